@@ -4,7 +4,10 @@ pub mod utils;
 use crate::{
     style::style::load_css,
     ui::ui::build_main_ui,
-    utils::logger::{LogLevel, Logger},
+    utils::{
+        logger::{LogLevel, Logger},
+        opensearch,
+    },
 };
 use clap::{ArgAction, Parser};
 use gtk::prelude::*;
@@ -30,6 +33,10 @@ struct Args {
     /// start with run command mode
     #[clap(short = 'r', long, action = ArgAction::SetTrue)]
     run: bool,
+
+    /// add a search engine from an OpenSearch description file or url
+    #[clap(long, value_name = "FILE|URL")]
+    add_engine: Option<String>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -44,6 +51,21 @@ async fn main() {
     let args = Args::parse();
     Logger::set_logging_enabled(args.debug);
 
+    if let Some(source) = args.add_engine {
+        match opensearch::import(&source).await {
+            Ok(engine) => println!(
+                "Added search engine {} ({})",
+                engine.name.unwrap_or_default(),
+                engine.id
+            ),
+            Err(e) => {
+                eprintln!("Failed to add search engine: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // determine start mode
     let start_mode = if args.web {
         StartMode::Web
@@ -1,3 +1,5 @@
+pub mod result_item;
+pub mod results;
 pub mod states;
 pub mod ui;
 pub mod ui_helper;
\ No newline at end of file
@@ -0,0 +1,111 @@
+use glib::subclass::prelude::*;
+
+/// what happens when a result row gets activated
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ResultAction {
+    #[default]
+    None,
+    /// launch desktop application by its id (desktop file stem)
+    Launch(String),
+    /// run a full command line
+    RunCommand(String),
+    /// run a command line and show its output
+    CaptureCommand(String),
+    /// run a command line inside a terminal emulator
+    RunInTerminal(String),
+    /// run a command line as root
+    RunElevated(String),
+    /// ask the process with this pid to quit
+    KillProcess(i32),
+    /// open url with the default handler
+    OpenUrl(String),
+    /// open file or directory with its default application
+    OpenPath(String),
+    /// copy text to clipboard
+    Copy(String),
+}
+
+mod imp {
+    use super::ResultAction;
+    use glib::Properties;
+    use glib::prelude::*;
+    use glib::subclass::prelude::*;
+    use std::cell::RefCell;
+
+    #[derive(Properties, Default)]
+    #[properties(wrapper_type = super::ResultItem)]
+    pub struct ResultItem {
+        #[property(get, set)]
+        title: RefCell<String>,
+        #[property(get, set)]
+        description: RefCell<String>,
+        #[property(get, set)]
+        caption: RefCell<String>,
+        #[property(get, set)]
+        icon_name: RefCell<String>,
+        #[property(get, set, nullable)]
+        paintable: RefCell<Option<gtk::gdk::Paintable>>,
+        /// extra css class for the title label
+        #[property(get, set)]
+        style: RefCell<String>,
+        /// text put into the search entry on Tab
+        #[property(get, set)]
+        completion: RefCell<String>,
+        pub action: RefCell<ResultAction>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ResultItem {
+        const NAME: &'static str = "StarlightResultItem";
+        type Type = super::ResultItem;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ResultItem {}
+}
+
+glib::wrapper! {
+    /// single row of the result list, shared by all modes
+    pub struct ResultItem(ObjectSubclass<imp::ResultItem>);
+}
+
+impl ResultItem {
+    pub fn new(title: &str, action: ResultAction) -> Self {
+        let item: Self = glib::Object::builder().property("title", title).build();
+        item.set_action(action);
+        item
+    }
+
+    pub fn with_description(self, description: &str) -> Self {
+        self.set_description(description);
+        self
+    }
+
+    pub fn with_caption(self, caption: &str) -> Self {
+        self.set_caption(caption);
+        self
+    }
+
+    pub fn with_icon(self, icon_name: &str) -> Self {
+        self.set_icon_name(icon_name);
+        self
+    }
+
+    pub fn with_style(self, style: &str) -> Self {
+        self.set_style(style);
+        self
+    }
+
+    pub fn with_completion(self, completion: &str) -> Self {
+        self.set_completion(completion);
+        self
+    }
+
+    pub fn action(&self) -> ResultAction {
+        self.imp().action.borrow().clone()
+    }
+
+    pub fn set_action(&self, action: ResultAction) {
+        self.imp().action.replace(action);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::ui::{result_item::ResultItem, ui_helper::create_result_factory};
+use gtk::{gio, prelude::*};
+
+/// virtualized result list shared by every mode
+///
+/// rows are recycled by the list view, so only the visible ones get widgets
+#[derive(Clone)]
+pub struct ResultList {
+    pub store: gio::ListStore,
+    pub selection: gtk::SingleSelection,
+    pub view: gtk::ListView,
+}
+
+impl Default for ResultList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResultList {
+    pub fn new() -> Self {
+        let store = gio::ListStore::new::<ResultItem>();
+        let selection = gtk::SingleSelection::new(Some(store.clone()));
+        selection.set_autoselect(true);
+        selection.set_can_unselect(false);
+
+        let view = gtk::ListView::new(Some(selection.clone()), Some(create_result_factory()));
+        view.set_vexpand(true);
+        view.set_single_click_activate(false);
+        view.add_css_class("apps-list");
+
+        Self {
+            store,
+            selection,
+            view,
+        }
+    }
+
+    /// replace all items in one go and select the first one
+    pub fn set_items(&self, items: &[ResultItem]) {
+        self.store.splice(0, self.store.n_items(), items);
+        if !items.is_empty() {
+            self.select(0);
+        }
+    }
+
+    pub fn append(&self, item: &ResultItem) {
+        self.store.append(item);
+    }
+
+    /// swap the item at the position for other items
+    pub fn replace(&self, position: u32, items: &[ResultItem]) {
+        if position < self.len() {
+            self.store.splice(position, 1, items);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.store.remove_all();
+    }
+
+    pub fn len(&self) -> u32 {
+        self.store.n_items()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn item(&self, position: u32) -> Option<ResultItem> {
+        self.store.item(position).and_downcast::<ResultItem>()
+    }
+
+    pub fn selected_item(&self) -> Option<ResultItem> {
+        self.selection.selected_item().and_downcast::<ResultItem>()
+    }
+
+    /// select and scroll to the given position
+    pub fn select(&self, position: u32) {
+        if position < self.len() {
+            self.selection.set_selected(position);
+            self.view
+                .scroll_to(position, gtk::ListScrollFlags::NONE, None);
+        }
+    }
+
+    pub fn select_next(&self) {
+        let selected = self.selection.selected();
+        if selected == gtk::INVALID_LIST_POSITION {
+            self.select(0);
+        } else {
+            self.select(selected + 1);
+        }
+    }
+
+    pub fn select_previous(&self) {
+        let selected = self.selection.selected();
+        if selected != gtk::INVALID_LIST_POSITION && selected > 0 {
+            self.select(selected - 1);
+        }
+    }
+}
@@ -1,19 +1,57 @@
-use crate::utils::applications::ApplicationManager;
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use crate::ui::result_item::ResultAction;
+use crate::utils::{
+    applications::ApplicationManager, bookmarks::Bookmark, browser_history::Visit,
+    command::ExecutableIndex, completion::CompletionShell, config::CONFIG, history::CommandHistory,
+    manpages::ManIndex, scripts::Script, search::SearchCoordinator,
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::RwLock;
 
 pub struct AppState {
     pub app_manager: Arc<RwLock<ApplicationManager>>,
-    pub filtered_apps: RefCell<Vec<crate::utils::applications::DesktopApplication>>,
-    pub current_search: RefCell<String>,
+    pub executables: Arc<RwLock<ExecutableIndex>>,
+    pub man_pages: Arc<RwLock<ManIndex>>,
+    pub scripts: Arc<RwLock<Vec<Script>>>,
+    pub bookmarks: Arc<RwLock<Vec<Bookmark>>>,
+    /// pages from the browser history, not to be confused with `history`
+    pub browser_history: Arc<RwLock<Vec<Visit>>>,
+    pub search: SearchCoordinator,
+    pub history: RefCell<CommandHistory>,
+    /// query and depth of the running Ctrl+R history recall
+    pub history_recall: RefCell<Option<(String, usize)>>,
+    /// dangerous command and the action that started it, waiting for Ctrl+Y
+    pub pending_dangerous: RefCell<Option<(String, ResultAction)>>,
+    /// dangerous command confirmed with Ctrl+Y, allowed to run once
+    pub confirmed_command: RefCell<Option<String>>,
+    pub completion_shell: CompletionShell,
+    /// a command is running with its output shown in the results
+    pub capture_running: Cell<bool>,
 }
 
 impl AppState {
     pub fn new() -> Rc<Self> {
         Rc::new(Self {
             app_manager: Arc::new(RwLock::new(ApplicationManager::new())),
-            filtered_apps: RefCell::new(Vec::new()),
-            current_search: RefCell::new(String::new()),
+            executables: Arc::new(RwLock::new(ExecutableIndex::new())),
+            man_pages: Arc::new(RwLock::new(ManIndex::default())),
+            scripts: Arc::new(RwLock::new(Vec::new())),
+            bookmarks: Arc::new(RwLock::new(Vec::new())),
+            browser_history: Arc::new(RwLock::new(Vec::new())),
+            search: SearchCoordinator::new(Duration::from_millis(CONFIG.search.debounce_ms)),
+            history: RefCell::new(CommandHistory::load(
+                CommandHistory::default_path(),
+                CONFIG.run.history_size,
+            )),
+            history_recall: RefCell::new(None),
+            pending_dangerous: RefCell::new(None),
+            confirmed_command: RefCell::new(None),
+            completion_shell: CompletionShell::detect(),
+            capture_running: Cell::new(false),
         })
     }
 }
@@ -1,26 +1,107 @@
 use crate::{
+    StartMode,
     ui::{
+        result_item::{ResultAction, ResultItem},
+        results::ResultList,
         states::AppState,
         ui_helper::{
-            create_app_row, create_icon_from_theme, create_web_search_row, scroll_to_selected,
+            create_app_item, create_bookmark_item, create_link_item, create_process_item,
+            create_script_item, create_suggestion_item, create_visit_item, create_web_search_item,
         },
-    }, utils::{
-        command::{get_executables_from_path, run_command}, logger::{LogLevel, Logger}, math_and_units::{copy_to_clipboard, try_math_expression, try_unit_conversion}, web::WebSearchManager
-    }, StartMode
+    },
+    utils::{
+        aliases,
+        applications::ApplicationManager,
+        bookmarks::{load_bookmarks, search_bookmarks},
+        browser,
+        browser_history::{load_history, search_history},
+        command::{
+            CaptureStatus, Executable, ExecutableIndex, capture_command, run_argv, run_command,
+        },
+        completion::{complete, escape_word},
+        config::CONFIG,
+        elevate::{ElevateError, Elevator, run_elevated},
+        links::{Link, detect_link},
+        logger::{LogLevel, Logger},
+        manpages::ManIndex,
+        math_and_units::{copy_to_clipboard, try_math_expression, try_unit_conversion},
+        processes::{Signal, fuzzy_score, list_processes, send_signal},
+        safeguard::{check_command, format_argv},
+        scripts::{load_scripts, scripts_dir, search_scripts},
+        shell::{import_login_env, shell_argv},
+        terminal::{Terminal, run_in_terminal},
+        web::WebSearchManager,
+    },
 };
 use adw::{ApplicationWindow, prelude::AdwApplicationWindowExt};
 use gtk::{
-    Box, Entry, EventControllerKey, Label, ListBox, PolicyType, ScrolledWindow, Spinner, gdk::Key,
+    Box, Entry, EventControllerKey, Label, PolicyType, ScrolledWindow, Spinner,
+    gdk::{Key, ModifierType},
     prelude::*,
 };
 use lazy_static::lazy_static;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc, time::Duration};
 
 lazy_static! {
     static ref LOG: Logger = Logger::new("ui", LogLevel::Debug);
 }
 
+const RUN_PREFIXES: &[&str] = &["r:", "run:"];
+const CAPTURE_PREFIXES: &[&str] = &["r!"];
+const ELEVATED_PREFIXES: &[&str] = &["sudo:"];
+const KILL_PREFIXES: &[&str] = &["kill:"];
+const WEB_PREFIXES: &[&str] = &["w:", "web:"];
+
+/// strip the first matching mode prefix from the query
+fn strip_mode_prefix<'a>(query: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes
+        .iter()
+        .find_map(|prefix| query.strip_prefix(prefix))
+}
+
+/// the part of the window below the search entry
+#[derive(Clone)]
+struct ResultsArea {
+    window: ApplicationWindow,
+    content: Box,
+    container: Box,
+    list: ResultList,
+    status_label: Label,
+}
+
+impl ResultsArea {
+    /// replace the results, or show the status message if there are none
+    fn show_items(&self, items: &[ResultItem], empty_message: &str) {
+        self.list.set_items(items);
+
+        if items.is_empty() {
+            self.status_label.set_text(empty_message);
+        }
+        self.status_label.set_visible(items.is_empty());
+        self.list.view.set_visible(!items.is_empty());
+        self.reveal();
+    }
+
+    fn show_status(&self, message: &str) {
+        self.show_items(&[], message);
+    }
+
+    fn reveal(&self) {
+        if self.container.parent().is_none() {
+            self.content.append(&self.container);
+            animate_window_height(&self.window, 80, 500);
+        }
+    }
+
+    fn hide(&self) {
+        self.list.clear();
+        if self.container.parent().is_some() {
+            self.content.remove(&self.container);
+            animate_window_height(&self.window, 500, 80);
+        }
+    }
+}
+
 pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> ApplicationWindow {
     let window = adw::ApplicationWindow::new(app);
     window.set_title(Some("starlight"));
@@ -28,18 +109,22 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
     window.set_size_request(600, 80);
     LOG.debug("window layer setup complete");
 
-    const PAGE_SIZE: usize = 50;
-
     // create app state
-    let app_state = Rc::new(AppState::new());
+    let app_state = AppState::new();
 
-    // Close app when presses ESCAPE button
+    // Close app when presses ESCAPE button, or stop the captured command
     let key_controller = EventControllerKey::new();
     let window_clone = window.clone();
+    let app_state_escape = app_state.clone();
     key_controller.connect_key_pressed(move |_controller, key, _keycode, _state| match key {
         Key::Escape => {
-            LOG.debug("application closed");
-            window_clone.close();
+            if app_state_escape.capture_running.get() {
+                LOG.debug("cancelling captured command");
+                app_state_escape.search.cancel();
+            } else {
+                LOG.debug("application closed");
+                window_clone.close();
+            }
             true.into()
         }
         _ => false.into(),
@@ -73,6 +158,7 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
     loading_box.set_halign(gtk::Align::Center);
     loading_box.set_valign(gtk::Align::Center);
     loading_box.set_vexpand(true);
+    loading_box.set_visible(false);
 
     let spinner = Spinner::new();
     spinner.set_spinning(true);
@@ -82,34 +168,42 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
     loading_box.append(&spinner);
     loading_box.append(&loading_label);
 
-    // list box holding apps
-    let list_box = ListBox::new();
-    list_box.set_vexpand(true);
-    list_box.add_css_class("apps-list");
+    // virtualized list holding the results of every mode
+    let result_list = ResultList::new();
 
-    // status label for when no apps are found
+    // status label for when no results are found
     let status_label = Label::new(Some("No applications found"));
     status_label.add_css_class("dim-label");
     status_label.set_halign(gtk::Align::Center);
     status_label.set_valign(gtk::Align::Center);
+    status_label.set_vexpand(true);
     status_label.set_visible(false);
 
-    let scroll_content = Box::new(gtk::Orientation::Vertical, 10);
-    scroll_content.append(&list_box);
-    scroll_content.append(&status_label);
-
     let scrolled_window = ScrolledWindow::new();
     scrolled_window.set_height_request(500);
     scrolled_window.set_hscrollbar_policy(PolicyType::Never);
     scrolled_window.set_vscrollbar_policy(PolicyType::Automatic);
     scrolled_window.set_css_classes(&["scrolled-window"]);
-    scrolled_window.set_child(Some(&scroll_content));
+    scrolled_window.set_child(Some(&result_list.view));
+
+    let results_container = Box::new(gtk::Orientation::Vertical, 10);
+    results_container.append(&loading_box);
+    results_container.append(&status_label);
+    results_container.append(&scrolled_window);
 
     content.append(&search_box);
 
     window.add_controller(key_controller);
     window.set_content(Some(&content));
 
+    let results = ResultsArea {
+        window: window.clone(),
+        content: content.clone(),
+        container: results_container,
+        list: result_list.clone(),
+        status_label: status_label.clone(),
+    };
+
     match start_mode {
         StartMode::Web => {
             prefix_label.set_text("web:");
@@ -120,7 +214,7 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
             search_entry.set_placeholder_text(Some("run: Run command..."));
         }
         StartMode::Default => {
-            scroll_content.append(&loading_box);
+            loading_box.set_visible(true);
             prefix_label.set_visible(false);
             search_entry.set_placeholder_text(Some("Search applications..."));
         }
@@ -128,101 +222,69 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
 
     // setup search functionality
     let app_state_search = app_state.clone();
-    let list_box_search = list_box.clone();
-    let status_label_search = status_label.clone();
-
-    let window_search = window.clone();
-    let content_search = content.clone();
-    let scrolled_window_search = scrolled_window.clone();
+    let results_search = results.clone();
     let prefix_clone = prefix_label.clone();
 
     search_entry.connect_changed(move |entry| {
         let query = format!("{}{}", prefix_clone.text(), entry.text());
+        let results = results_search.clone();
 
-        if let Some((result_str, icon_name)) = try_math_expression(&query) {
-            while let Some(child) = list_box_search.first_child() {
-                list_box_search.remove(&child);
-            }
-
-            let row = gtk::ListBoxRow::new();
-            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 8);
-
-            let icon = create_icon_from_theme(icon_name);
-            icon.add_css_class("dim-icon");
-            let label = gtk::Label::new(Some(&result_str));
-            label.set_halign(gtk::Align::Start);
-            label.add_css_class("math-result");
-
-            hbox.append(&icon);
-            hbox.append(&label);
-            row.set_child(Some(&hbox));
-            row.add_css_class("card");
-            row.set_activatable(true);
-
-            let result_clone = result_str.clone();
-            row.connect_activate(move |_| {
-                copy_to_clipboard(&result_clone);
-                LOG.debug("Copied math result to clipboard");
-            });
-
-            list_box_search.append(&row);
-            list_box_search.set_visible(true);
-            status_label_search.set_visible(false);
+        // every keystroke supersedes the searches still running for older queries
+        let coordinator = app_state_search.search.clone();
+        let token = coordinator.begin();
+        app_state_search.history_recall.replace(None);
+        app_state_search.pending_dangerous.replace(None);
+        app_state_search.confirmed_command.replace(None);
 
-            if scrolled_window_search.parent().is_none() {
-                content_search.append(&scrolled_window_search);
+        if let Some((result_str, icon_name)) = try_math_expression(&query) {
+            let item = ResultItem::new(&result_str, ResultAction::Copy(result_str.clone()))
+                .with_icon(icon_name)
+                .with_style("math-result");
+            results.show_items(&[item], "");
+            return;
+        } else if let Some(conversion) = try_unit_conversion(&query) {
+            match conversion {
+                Ok((result_str, icon_name)) => {
+                    let item = ResultItem::new(&result_str, ResultAction::Copy(result_str.clone()))
+                        .with_icon(icon_name)
+                        .with_style("unit-result");
+                    results.show_items(&[item], "");
+                }
+                Err(e) => results.show_status(&format!("Can't convert: {}", e)),
             }
-            scrolled_window_search.set_visible(true);
-            animate_window_height(&window_search, 80, 200);
-
             return;
-        } else if let Some((result_str, icon_name)) = try_unit_conversion(&query) {
+        }
 
-            while let Some(child) = list_box_search.first_child() {
-                list_box_search.remove(&child);
+        if let Some(cmd_query) = strip_mode_prefix(&query, CAPTURE_PREFIXES) {
+            let cmd_query = cmd_query.trim();
+            if cmd_query.is_empty() {
+                results.show_status("Enter a command to run and show its output.");
+                return;
             }
 
-            let row = gtk::ListBoxRow::new();
-            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 8);
-
-            let icon = create_icon_from_theme(icon_name);
-            icon.add_css_class("dim-icon");
-            let label = gtk::Label::new(Some(&result_str));
-            label.set_halign(gtk::Align::Start);
-            label.add_css_class("unit-result");
-
-            hbox.append(&icon);
-            hbox.append(&label);
-            row.set_child(Some(&hbox));
-            row.add_css_class("card");
-            row.set_activatable(true);
-
-            let result_clone = result_str.clone();
-            row.connect_activate(move |_| {
-                copy_to_clipboard(&result_clone);
-                LOG.debug("Copied unit conversion result to clipboard");
-            });
-
-            list_box_search.append(&row);
-
-            list_box_search.set_visible(true);
-            status_label_search.set_visible(false);
-
-            if scrolled_window_search.parent().is_none() {
-                content_search.append(&scrolled_window_search);
+            let item = ResultItem::new(
+                &format!("Run '{}' and show output", cmd_query),
+                ResultAction::CaptureCommand(cmd_query.to_string()),
+            )
+            .with_description("Output is shown here, Escape stops the command")
+            .with_icon("utilities-terminal");
+            results.show_items(&[item], "");
+        } else if let Some(cmd_query) = strip_mode_prefix(&query, ELEVATED_PREFIXES) {
+            let cmd_query = cmd_query.trim();
+            if cmd_query.is_empty() {
+                results.show_status("Enter a command to run as root.");
+                return;
             }
-            scrolled_window_search.set_visible(true);
-            animate_window_height(&window_search, 80, 200);
-
-            return;
-        }
+            let Some(elevator) = Elevator::detect() else {
+                results.show_status(&format!("Can't run as root: {}", ElevateError::Unavailable));
+                return;
+            };
 
-        if query.starts_with("r:") || query.starts_with("run:") {
-            let cmd_query = query
-                .trim_start_matches("r:")
-                .trim_start_matches("run:")
-                .trim()
-                .to_string();
+            results.show_items(&[elevated_item(cmd_query, &elevator)], "");
+        } else if let Some(cmd_query) = strip_mode_prefix(&query, RUN_PREFIXES) {
+            // keep the trailing space, it starts a new word to complete
+            let cmd_line = cmd_query.trim_start().to_string();
+            let cmd_query = cmd_query.trim().to_string();
 
             let cmd_name = cmd_query
                 .split_whitespace()
@@ -230,381 +292,464 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
                 .unwrap_or_default()
                 .to_string();
 
-            let list_box_clone = list_box_search.clone();
-            let status_label_clone = status_label_search.clone();
-            let content_clone = content_search.clone();
-            let scrolled_window_clone_query = scrolled_window_search.clone();
-            let window_clone2 = window_search.clone();
+            // previously run command lines come first, most recent on top
+            let history_items = app_state_search
+                .history
+                .borrow()
+                .search(&cmd_query)
+                .into_iter()
+                .map(|command| {
+                    ResultItem::new(command, ResultAction::RunCommand(command.clone()))
+                        .with_description("From history")
+                        .with_icon("document-open-recent")
+                        .with_completion(command)
+                })
+                .collect::<Vec<_>>();
+
+            // a typed url opens directly, paths are commands here
+            let link_items = link_items(&cmd_query, false);
+
+            let executables = app_state_search.executables.clone();
+            let man_pages = app_state_search.man_pages.clone();
+            let completion_shell = app_state_search.completion_shell.clone();
 
             glib::spawn_future_local(async move {
-                let commands = get_executables_from_path().await;
-                let filtered = commands
-                    .iter()
-                    .filter(|cmd| cmd.contains(&cmd_name))
-                    .take(1000)
-                    .cloned()
-                    .collect::<Vec<_>>();
-
-                let total_filtered = filtered.len();
-                let commands_to_show = &filtered[..PAGE_SIZE.min(total_filtered)];
-
-                while let Some(child) = list_box_clone.first_child() {
-                    list_box_clone.remove(&child);
-                }
-
-                if filtered.is_empty() {
-                    status_label_clone.set_text(&format!("No matching commands '{}'", cmd_query));
-                    status_label_clone.set_visible(true);
-                    list_box_clone.set_visible(false);
-                } else {
-                    status_label_clone.set_visible(false);
-                    list_box_clone.set_visible(true);
-
-                    for cmd in commands_to_show {
-                        let row = gtk::ListBoxRow::new();
-                        row.set_widget_name(&cmd);
-                        let label = Label::new(Some(&cmd));
-                        label.set_halign(gtk::Align::Start);
-                        label.add_css_class("title");
-                        row.set_child(Some(&label));
-                        row.add_css_class("card");
-                        row.set_activatable(true);
-                        list_box_clone.append(&row);
-                    }
-
-                    if let Some(first_row) = list_box_clone.row_at_index(0) {
-                        list_box_clone.select_row(Some(&first_row));
-                        scroll_to_selected(&list_box_clone, &scrolled_window_clone_query);
-                    }
-
-                    let filtered_commands = Rc::new(RefCell::new(filtered.clone()));
-                    let offset = Rc::new(RefCell::new(PAGE_SIZE.min(filtered.len())));
-
-                    let list_box_clone2 = list_box_clone.clone();
-                    let filtered_commands_clone = filtered_commands.clone();
-                    let offset_clone = offset.clone();
-                    let scrolled_window_clone = scrolled_window_clone_query.clone();
-
-                    scrolled_window_clone
-                        .vadjustment()
-                        .connect_value_changed(move |adjustment| {
-                            let threshold = 30.0;
-                            if adjustment.value() + adjustment.page_size()
-                                >= adjustment.upper() - threshold
-                            {
-                                let offset_val = *offset_clone.borrow();
-                                let filtered = filtered_commands_clone.borrow();
-
-                                if offset_val < filtered.len() {
-                                    let next_offset = (offset_val + PAGE_SIZE).min(filtered.len());
-
-                                    for cmd in &filtered[offset_val..next_offset] {
-                                        let row = gtk::ListBoxRow::new();
-                                        row.set_widget_name(&cmd);
-                                        let label = Label::new(Some(&cmd));
-                                        label.set_halign(gtk::Align::Start);
-                                        label.add_css_class("title");
-                                        row.set_child(Some(&label));
-                                        row.add_css_class("card");
-                                        row.set_activatable(true);
-                                        list_box_clone2.append(&row);
-                                    }
-
-                                    *offset_clone.borrow_mut() = next_offset;
-                                }
-                            }
+                let search = async {
+                    // paths and arguments of the word being typed
+                    let completion_items = complete(&cmd_line, &completion_shell)
+                        .await
+                        .into_iter()
+                        .map(|completion| {
+                            ResultItem::new(
+                                &completion.word,
+                                ResultAction::RunCommand(completion.line.clone()),
+                            )
+                            .with_description(completion.description.as_deref().unwrap_or(""))
+                            .with_caption(&completion.line)
+                            .with_completion(&completion.line)
+                        })
+                        .collect::<Vec<_>>();
+
+                    // aliases and functions shadow executables of the same name
+                    let definition_items = aliases::search(&cmd_name)
+                        .into_iter()
+                        .map(|definition| {
+                            // the alias shown runs, with the typed arguments
+                            let args = cmd_line
+                                .find(char::is_whitespace)
+                                .map_or(" ", |index| &cmd_line[index..]);
+                            let line = format!("{}{}", definition.name, args);
+                            ResultItem::new(
+                                &definition.name,
+                                ResultAction::RunCommand(line.trim().to_string()),
+                            )
+                            .with_description(&definition.expansion)
+                            .with_caption(definition.kind.label())
+                            .with_icon("text-x-script")
+                            .with_completion(&line)
+                        })
+                        .collect::<Vec<_>>();
+
+                    ExecutableIndex::refresh_shared(&executables).await;
+                    let index = executables.read().await;
+                    let man_pages = man_pages.read().await;
+                    // complete the first word, keeping the typed arguments
+                    let args = cmd_line
+                        .find(char::is_whitespace)
+                        .map_or(" ", |index| &cmd_line[index..]);
+                    let mut executable_items = index
+                        .search(&cmd_name)
+                        .into_iter()
+                        .map(|executable| {
+                            executable_item(
+                                executable,
+                                man_pages.description(&executable.name),
+                                &format!("{}{}", executable.name, args),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+
+                    // discover commands by what they do, e.g. "disk usage"
+                    let described_items = man_pages
+                        .search_descriptions(&cmd_query)
+                        .into_iter()
+                        .filter(|name| !name.contains(cmd_name.as_str()))
+                        .filter_map(|name| index.get(name))
+                        .map(|executable| {
+                            executable_item(
+                                executable,
+                                man_pages.description(&executable.name),
+                                &format!("{} ", executable.name),
+                            )
                         });
-                }
+                    executable_items.extend(described_items);
+
+                    link_items
+                        .into_iter()
+                        .chain(completion_items)
+                        .chain(history_items)
+                        .chain(definition_items)
+                        .chain(executable_items)
+                        .collect::<Vec<_>>()
+                };
 
-                if scrolled_window_clone_query.parent().is_none() {
-                    content_clone.append(&scrolled_window_clone_query);
+                if let Some(items) = coordinator.run(&token, search).await {
+                    results.show_items(&items, &format!("No matching commands '{}'", cmd_query));
                 }
-                scrolled_window_clone_query.set_visible(true);
-                animate_window_height(&window_clone2, 80, 500);
             });
-        } else if query.starts_with("w:") || query.starts_with("web:") {
-            let web_query = query
-                .trim_start_matches("w:")
-                .trim_start_matches("web:")
-                .trim()
-                .to_string();
-
-            let web_list_box = list_box_search.clone();
-            let web_status_label = status_label_search.clone();
-            let web_content = content_search.clone();
-            let web_scrolled_window = scrolled_window_search.clone();
-            let web_window = window_search.clone();
+        } else if let Some(process_query) = strip_mode_prefix(&query, KILL_PREFIXES) {
+            let process_query = process_query.trim().to_string();
+            let app_manager = app_state_search.app_manager.clone();
 
             glib::spawn_future_local(async move {
-                let web_manager = WebSearchManager::new();
-                let search_results = if !web_query.is_empty() {
-                    web_manager.search_engines_for_query(&web_query)
-                } else {
-                    web_status_label.set_text("Enter your query to search on web.");
-                    Vec::new()
+                let search = async {
+                    let mut processes = list_processes()
+                        .await
+                        .into_iter()
+                        .filter_map(|process| {
+                            let score = if process_query.is_empty() {
+                                0
+                            } else {
+                                fuzzy_score(&process_query, &process.name).or_else(|| {
+                                    fuzzy_score(&process_query, &process.command_line)
+                                })?
+                            };
+                            Some((score, process))
+                        })
+                        .collect::<Vec<_>>();
+                    // best match first, the busiest on top when equally good
+                    processes.sort_by(|(a_score, a), (b_score, b)| {
+                        b_score
+                            .cmp(a_score)
+                            .then(b.cpu_percent.total_cmp(&a.cpu_percent))
+                    });
+
+                    let icons = app_icons(&*app_manager.read().await);
+                    processes
+                        .iter()
+                        .map(|(_, process)| {
+                            let icon = process
+                                .program_names()
+                                .iter()
+                                .find_map(|name| icons.get(&name.to_lowercase()));
+                            create_process_item(process, icon.map(String::as_str))
+                        })
+                        .collect::<Vec<_>>()
                 };
 
-                while let Some(child) = web_list_box.first_child() {
-                    web_list_box.remove(&child);
-                }
-
-                if web_query.is_empty() {
-                    web_status_label.set_visible(true);
-                    web_list_box.set_visible(false);
-                } else {
-                    web_status_label.set_visible(false);
-                    web_list_box.set_visible(true);
+                if let Some(items) = coordinator.run(&token, search).await {
+                    results.show_items(
+                        &items,
+                        &format!("No matching processes '{}'", process_query),
+                    );
                 }
+            });
+        } else if let Some(web_query) = strip_mode_prefix(&query, WEB_PREFIXES) {
+            let web_query = web_query.trim().to_string();
 
-                for result in search_results {
-                    let row = create_web_search_row(&result, &web_query);
-                    web_list_box.append(&row);
-                }
+            if web_query.is_empty() {
+                results.show_status("Enter your query to search on web.");
+                return;
+            }
 
-                if let Some(first_row) = web_list_box.row_at_index(0) {
-                    web_list_box.select_row(Some(&first_row));
-                    scroll_to_selected(&web_list_box, &web_scrolled_window);
-                }
+            let web_manager = WebSearchManager::new();
+            let engine_items = web_manager
+                .results_for_query(&web_query)
+                .iter()
+                .map(create_web_search_item)
+                .collect::<Vec<_>>();
+            let items = link_items(&web_query, true)
+                .into_iter()
+                .chain(engine_items)
+                .collect::<Vec<_>>();
+            results.show_items(&items, "");
+
+            if !CONFIG.search.suggestions {
+                return;
+            }
 
-                if web_scrolled_window.parent().is_none() {
-                    web_content.append(&web_scrolled_window);
+            // suggestions come in below the engine rows once the engines answered
+            glib::spawn_future_local(async move {
+                let search = web_manager.suggestions(&web_query);
+                if let Some(suggestions) = coordinator.run(&token, search).await {
+                    for suggestion in &suggestions {
+                        results.list.append(&create_suggestion_item(suggestion));
+                    }
                 }
-                web_scrolled_window.set_visible(true);
-                animate_window_height(&web_window, 80, 500);
             });
         } else {
-            app_state_search.current_search.replace(query.clone());
-            let scrolled_window_clone = scrolled_window_search.clone();
-
             if query.is_empty() {
-                if scrolled_window_clone.parent().is_none() {
-                    content_search.remove(&scrolled_window_clone);
-                }
-                scrolled_window_clone.set_visible(false);
-                animate_window_height(&window_search, 500, 80);
+                results.hide();
+                return;
+            }
+
+            // `!so borrow checker` searches the web right from the default mode
+            if query.starts_with('!')
+                && let Some(result) = WebSearchManager::new().bang_result(&query)
+            {
+                results.show_items(&[create_web_search_item(&result)], "");
                 return;
-            } else {
-                if !scrolled_window_clone.parent().is_some() {
-                    content_search.append(&scrolled_window_clone);
-                }
-                scrolled_window_clone.set_visible(true);
-                animate_window_height(&window_search, 80, 500);
             }
 
-            // update the list based on search
+            // update the list based on search, a typed url or path on top
+            let link_items = link_items(&query, true);
             let manager = app_state_search.app_manager.clone();
-            let list_box_clone = list_box_search.clone();
-            let status_label_clone = status_label_search.clone();
+            let scripts = app_state_search.scripts.clone();
+            let bookmarks = app_state_search.bookmarks.clone();
+            let browser_history = app_state_search.browser_history.clone();
 
             glib::spawn_future_local(async move {
-                let manager = manager.read().await;
-                let apps = if query.is_empty() {
-                    manager.get_applications()
-                } else {
-                    manager.search_applications(&query)
+                let search = async {
+                    let manager = manager.read().await;
+                    let scripts = scripts.read().await;
+                    let bookmarks = bookmarks.read().await;
+                    let browser_history = browser_history.read().await;
+                    let script_items = search_scripts(&scripts, &query)
+                        .into_iter()
+                        .map(create_script_item);
+                    let bookmark_matches = search_bookmarks(&bookmarks, &query);
+                    // bookmarked pages are listed once, as the bookmark
+                    let visit_items = search_history(&browser_history, &query)
+                        .into_iter()
+                        .filter(|visit| {
+                            !bookmark_matches
+                                .iter()
+                                .any(|bookmark| bookmark.url == visit.url)
+                        })
+                        .map(create_visit_item)
+                        .collect::<Vec<_>>();
+                    let bookmark_items = bookmark_matches.into_iter().map(create_bookmark_item);
+                    let app_items = manager
+                        .search_applications(&query)
+                        .into_iter()
+                        .map(create_app_item);
+                    let items = link_items
+                        .into_iter()
+                        .chain(app_items)
+                        .chain(script_items)
+                        .chain(bookmark_items)
+                        .chain(visit_items)
+                        .collect::<Vec<_>>();
+                    (items, manager.count() + scripts.len())
                 };
 
-                // clear existing items
-                while let Some(child) = list_box_clone.first_child() {
-                    list_box_clone.remove(&child);
-                }
-
-                if apps.is_empty() {
-                    status_label_clone.set_visible(true);
-                    list_box_clone.set_visible(false);
-                    if query.is_empty() {
-                        status_label_clone.set_text("No applications installed");
+                if let Some((items, count)) = coordinator.run(&token, search).await {
+                    let empty_message = if count == 0 {
+                        "No applications installed".to_string()
                     } else {
-                        status_label_clone
-                            .set_text(&format!("No applications found for '{}'", query));
-                    }
-                } else {
-                    status_label_clone.set_visible(false);
-                    list_box_clone.set_visible(true);
-
-                    for app in apps {
-                        let row = create_app_row(app);
-                        list_box_clone.append(&row);
-                    }
-
-                    if let Some(first_row) = list_box_clone.row_at_index(0) {
-                        list_box_clone.select_row(Some(&first_row));
-                        scroll_to_selected(&list_box_clone, &scrolled_window_clone);
-                    }
+                        format!("No applications found for '{}'", query)
+                    };
+                    results.show_items(&items, &empty_message);
                 }
             });
         }
     });
 
     let main_controller = EventControllerKey::new();
-    let list_box_nav = list_box.clone();
-    let scrolled_window_nav = scrolled_window.clone();
+    let result_list_nav = result_list.clone();
+    let app_state_nav = app_state.clone();
     let search_entry_nav = search_entry.clone();
-
-    main_controller.connect_key_pressed(move |_controller, key, _keycode, _state| match key {
-        gtk::gdk::Key::Down => {
-            if let Some(selected_row) = list_box_nav.selected_row() {
-                let index = selected_row.index();
-                if let Some(next_row) = list_box_nav.row_at_index(index + 1) {
-                    list_box_nav.select_row(Some(&next_row));
-                    scroll_to_selected(&list_box_nav, &scrolled_window_nav);
-                }
-            } else {
-                if let Some(first_row) = list_box_nav.row_at_index(0) {
-                    list_box_nav.select_row(Some(&first_row));
-                    scroll_to_selected(&list_box_nav, &scrolled_window_nav);
-                }
-            }
+    let prefix_nav = prefix_label.clone();
+    let results_nav = results.clone();
+
+    // capture phase, so Tab completes before moving the focus
+    main_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    main_controller.connect_key_pressed(move |_controller, key, _keycode, state| match key {
+        Key::Down => {
+            result_list_nav.select_next();
             glib::Propagation::Stop
         }
-        gtk::gdk::Key::Up => {
-            if let Some(selected_row) = list_box_nav.selected_row() {
-                let index = selected_row.index();
-                if index > 0 {
-                    if let Some(prev_row) = list_box_nav.row_at_index(index - 1) {
-                        list_box_nav.select_row(Some(&prev_row));
-                        scroll_to_selected(&list_box_nav, &scrolled_window_nav);
-                    }
+        Key::Up => {
+            result_list_nav.select_previous();
+            glib::Propagation::Stop
+        }
+        Key::Tab => {
+            if let Some(item) = result_list_nav.selected_item() {
+                let completion = item.completion();
+                if !completion.is_empty() {
+                    set_run_command(&search_entry_nav, &prefix_nav.text(), &completion);
                 }
             }
             glib::Propagation::Stop
         }
-        gtk::gdk::Key::Return => {
-            if search_entry_nav.has_focus() {
-                if let Some(first_row) = list_box_nav.row_at_index(0) {
-                    first_row.activate();
-                }
-            } else {
-                if let Some(selected_row) = list_box_nav.selected_row() {
-                    selected_row.activate();
-                } else if let Some(first_row) = list_box_nav.row_at_index(0) {
-                    first_row.activate();
+        Key::Return | Key::KP_Enter if state.contains(ModifierType::CONTROL_MASK) => {
+            // run inside a terminal, Shift keeps it open after the command exits
+            let Some(command) =
+                selected_run_command(&result_list_nav, &search_entry_nav, &prefix_nav.text())
+            else {
+                return glib::Propagation::Proceed;
+            };
+            let hold = CONFIG.run.hold_terminal || state.contains(ModifierType::SHIFT_MASK);
+            terminal_command(&command, hold, &app_state_nav, &results_nav);
+            glib::Propagation::Stop
+        }
+        Key::Return | Key::KP_Enter
+            if state.contains(ModifierType::SHIFT_MASK)
+                && let Some(ResultAction::KillProcess(pid)) =
+                    result_list_nav.selected_item().map(|item| item.action()) =>
+        {
+            kill_process(pid, Signal::Kill, &results_nav);
+            glib::Propagation::Stop
+        }
+        Key::Return | Key::KP_Enter
+            if state.contains(ModifierType::SHIFT_MASK)
+                && let Some(ResultAction::OpenUrl(url)) =
+                    result_list_nav.selected_item().map(|item| item.action()) =>
+        {
+            open_url(url, true, &app_state_nav, &results_nav);
+            glib::Propagation::Stop
+        }
+        Key::Return | Key::KP_Enter if state.contains(ModifierType::SHIFT_MASK) => {
+            // capture the output of the command instead of running it headless
+            let Some(command) =
+                selected_run_command(&result_list_nav, &search_entry_nav, &prefix_nav.text())
+            else {
+                return glib::Propagation::Proceed;
+            };
+            capture_output(command, &app_state_nav, &results_nav);
+            glib::Propagation::Stop
+        }
+        Key::F1 => {
+            // open the man page of the selected command
+            let Some(command) =
+                selected_run_command(&result_list_nav, &search_entry_nav, &prefix_nav.text())
+            else {
+                return glib::Propagation::Proceed;
+            };
+            let name = command.split_whitespace().next().unwrap_or_default();
+
+            match run_in_terminal(&format!("man {}", escape_word(name)), false) {
+                Ok(_) => results_nav.window.close(),
+                Err(e) => {
+                    results_nav.show_status(&format!("Failed to open a terminal: {}", e));
                 }
             }
             glib::Propagation::Stop
         }
+        Key::Return | Key::KP_Enter if state.contains(ModifierType::ALT_MASK) => {
+            // run as root
+            let Some(command) =
+                selected_run_command(&result_list_nav, &search_entry_nav, &prefix_nav.text())
+            else {
+                return glib::Propagation::Proceed;
+            };
+            elevate_command(&command, &app_state_nav, &results_nav);
+            glib::Propagation::Stop
+        }
+        Key::r if state.contains(ModifierType::CONTROL_MASK) => {
+            recall_history(&app_state_nav, &search_entry_nav, &prefix_nav.text());
+            glib::Propagation::Stop
+        }
+        Key::y if state.contains(ModifierType::CONTROL_MASK) => {
+            // run the dangerous command the warning is shown for
+            let Some((command, action)) = app_state_nav.pending_dangerous.take() else {
+                return glib::Propagation::Proceed;
+            };
+            app_state_nav.confirmed_command.replace(Some(command));
+            activate_item(&ResultItem::new("", action), &app_state_nav, &results_nav);
+            glib::Propagation::Stop
+        }
         _ => glib::Propagation::Proceed,
     });
 
     window.add_controller(main_controller);
 
-    let list_box_clone = list_box.clone();
+    // Set up activation of the selected result
+    let app_state_launch = app_state.clone();
+    let results_launch = results.clone();
+
     search_entry.connect_activate(move |_| {
-        if let Some(selected_row) = list_box_clone.selected_row() {
-            selected_row.activate();
-        } else if let Some(first_row) = list_box_clone.row_at_index(0) {
-            first_row.activate();
+        let list = &results_launch.list;
+        if let Some(item) = list.selected_item().or_else(|| list.item(0)) {
+            activate_item(&item, &app_state_launch, &results_launch);
         }
     });
 
-    // Set up app launch functionality
     let app_state_launch = app_state.clone();
-    let window_launch = window.clone();
-    let search_entry_launch = search_entry.clone();
-
-    list_box.connect_row_activated(move |_list_box, row| {
-        let search_entry_clone = search_entry_launch.clone();
-        let query = format!("{}{}", prefix_label.text(), search_entry_clone.text());
-
-        if query.starts_with("r:") || query.starts_with("run:") {
-            let full_cmd = query
-                .trim_start_matches("r:")
-                .trim_start_matches("run:")
-                .trim();
-
-            run_command(full_cmd);
-            window_launch.close();
-        } else if query.starts_with("w:") || query.starts_with("web:") {
-            let url = row.widget_name().to_string();
-            let web_manager = WebSearchManager::new();
-
-            match web_manager.open_url(&url) {
-                Ok(_) => {
-                    LOG.debug(&format!("Opened URL: {}", url));
-                    window_launch.close();
-                }
-                Err(e) => {
-                    LOG.error(&format!("Failed to open URL: {:?}", e));
-                    window_launch.close();
-                }
-            }
-        } else {
-            if let Some(app_name) = Some(row.widget_name().to_string()) {
-                let manager = app_state_launch.app_manager.clone();
-                let app_name = app_name.to_string();
-                let window_to_close = window_launch.clone();
+    let results_launch = results.clone();
 
-                glib::spawn_future_local(async move {
-                    let manager = manager.read().await;
-                    if let Some(app) = manager.get_application(&app_name) {
-                        match manager.launch_application(app).await {
-                            Ok(_) => {
-                                LOG.debug(&format!("launched {} sucessfully", app_name));
-                                window_to_close.close();
-                            }
-                            Err(e) => {
-                                LOG.error(&format!("Failed to launch application: {:?}", e));
-                                window_to_close.close();
-                            }
-                        }
-                    }
-                });
-            }
+    result_list.view.connect_activate(move |_view, position| {
+        if let Some(item) = results_launch.list.item(position) {
+            activate_item(&item, &app_state_launch, &results_launch);
         }
     });
 
-    if let Some(first_row) = list_box.row_at_index(0) {
-        list_box.select_row(Some(&first_row));
-    }
+    // import the login shell environment, then index PATH executables,
+    // shell aliases and man pages in the background so run mode is ready when needed
+    let executables_load = app_state.executables.clone();
+    let man_pages_load = app_state.man_pages.clone();
+    glib::spawn_future_local(async move {
+        if let Some(path) = import_login_env().await.and_then(|env| env.get("PATH")) {
+            executables_load.write().await.set_path_var(path);
+        }
+        // the interactive shell is slow to start, don't hold the index back for it
+        glib::spawn_future_local(aliases::refresh());
+        glib::spawn_future_local(async move {
+            *man_pages_load.write().await = ManIndex::load().await;
+        });
+        ExecutableIndex::refresh_shared(&executables_load).await;
+    });
 
     if start_mode == StartMode::Default {
+        let scripts_load = app_state.scripts.clone();
+        glib::spawn_future_local(async move {
+            if let Ok(scripts) = tokio::task::spawn_blocking(|| load_scripts(&scripts_dir())).await
+            {
+                *scripts_load.write().await = scripts;
+            }
+        });
+
+        if CONFIG.web.bookmarks {
+            let bookmarks_load = app_state.bookmarks.clone();
+            glib::spawn_future_local(async move {
+                if let Ok(bookmarks) = tokio::task::spawn_blocking(load_bookmarks).await {
+                    *bookmarks_load.write().await = bookmarks;
+                }
+            });
+        }
+
+        if CONFIG.web.history {
+            let history_load = app_state.browser_history.clone();
+            glib::spawn_future_local(async move {
+                let days = CONFIG.web.history_days;
+                if let Ok(visits) = tokio::task::spawn_blocking(move || load_history(days)).await {
+                    *history_load.write().await = visits;
+                }
+            });
+        }
+
         // load applications asynchronously
         let app_state_load = app_state.clone();
         let search_entry_load = search_entry.clone();
         let loading_box_load = loading_box.clone();
-        let list_box_load = list_box.clone();
-        let status_label_load = status_label.clone();
-        let scrolled_window_load = scrolled_window.clone();
+        let results_load = results.clone();
 
         glib::spawn_future_local(async move {
             LOG.debug("Starting application loading...");
 
             let mut manager = app_state_load.app_manager.write().await;
-            match manager.load_applications().await {
-                Ok(_) => {
-                    LOG.debug(&format!(
-                        "Successfully loaded {} applications",
-                        manager.count()
-                    ));
-
-                    loading_box_load.set_visible(false);
+            let loaded = manager.load_applications().await;
+            let count = manager.count();
+            drop(manager);
 
-                    // show the loaded list
-                    list_box_load.set_visible(true);
+            loading_box_load.set_visible(false);
 
-                    if let Some(first_row) = list_box_load.row_at_index(0) {
-                        list_box_load.select_row(Some(&first_row));
-                        scroll_to_selected(&list_box_load, &scrolled_window_load);
-                    }
+            match loaded {
+                Ok(_) => {
+                    LOG.debug(&format!("Successfully loaded {} applications", count));
 
-                    // Trigger search after loading if we have a prefix
+                    // Trigger search after loading if we have a prefilled query
                     let current_text = search_entry_load.text().to_string();
                     if !current_text.is_empty() {
-                        search_entry_load.emit_activate();
+                        search_entry_load.emit_by_name::<()>("changed", &[]);
+                        search_entry_load.set_position(-1);
                     }
 
                     search_entry_load.grab_focus();
-
-                    // Position cursor at end if we have prefilled text
-                    if !current_text.is_empty() {
-                        search_entry_load.set_position(-1);
-                    }
                 }
                 Err(e) => {
                     LOG.error(&format!("Failed to load applications: {:?}", e));
-                    loading_box_load.set_visible(false);
-                    status_label_load.set_text("Failed to load applications");
-                    status_label_load.set_visible(true);
+                    results_load.show_status("Failed to load applications");
                 }
             }
         });
@@ -614,6 +759,371 @@ pub fn build_main_ui(app: &adw::Application, start_mode: StartMode) -> Applicati
     window
 }
 
+/// run the action behind a result row
+fn activate_item(item: &ResultItem, app_state: &Rc<AppState>, results: &ResultsArea) {
+    let window = &results.window;
+    match item.action() {
+        ResultAction::None => {}
+        ResultAction::Copy(text) => {
+            copy_to_clipboard(&text);
+            LOG.debug("Copied result to clipboard");
+        }
+        ResultAction::RunCommand(command) => {
+            if !confirm_dangerous(
+                &command,
+                &run_argv(&command),
+                item.action(),
+                app_state,
+                results,
+            ) {
+                return;
+            }
+            run_command(&command);
+            app_state.history.borrow_mut().add(&command);
+            window.close();
+        }
+        ResultAction::CaptureCommand(command) => {
+            capture_output(command, app_state, results);
+        }
+        ResultAction::RunInTerminal(command) => {
+            terminal_command(&command, CONFIG.run.hold_terminal, app_state, results);
+        }
+        ResultAction::RunElevated(command) => {
+            elevate_command(&command, app_state, results);
+        }
+        ResultAction::KillProcess(pid) => {
+            kill_process(pid, Signal::Terminate, results);
+        }
+        ResultAction::OpenUrl(url) => {
+            open_url(url, false, app_state, results);
+        }
+        ResultAction::OpenPath(path) => {
+            let uri = gtk::gio::File::for_path(&path).uri();
+            match gtk::gio::AppInfo::launch_default_for_uri(
+                &uri,
+                None::<&gtk::gio::AppLaunchContext>,
+            ) {
+                Ok(_) => LOG.debug(&format!("Opened path: {}", path)),
+                Err(e) => LOG.error(&format!("Failed to open path: {:?}", e)),
+            }
+            window.close();
+        }
+        ResultAction::Launch(app_name) => {
+            let manager = app_state.app_manager.clone();
+            let window_to_close = window.clone();
+
+            glib::spawn_future_local(async move {
+                let manager = manager.read().await;
+                if let Some(app) = manager.get_application(&app_name) {
+                    match manager.launch_application(app).await {
+                        Ok(_) => {
+                            LOG.debug(&format!("launched {} sucessfully", app_name));
+                        }
+                        Err(e) => {
+                            LOG.error(&format!("Failed to launch application: {:?}", e));
+                        }
+                    }
+                    window_to_close.close();
+                }
+            });
+        }
+    }
+}
+
+/// row running the command as root, marked so it can't be mistaken for a normal run
+fn elevated_item(command: &str, elevator: &Elevator) -> ResultItem {
+    ResultItem::new(
+        &format!("Run '{}' as root", command),
+        ResultAction::RunElevated(command.to_string()),
+    )
+    .with_description(&format!("Asks for your password with {}", elevator.name()))
+    .with_caption("Runs with full system access")
+    .with_icon("dialog-warning-symbolic")
+    .with_style("elevated-command")
+}
+
+fn elevate_command(command: &str, app_state: &AppState, results: &ResultsArea) {
+    let argv = Elevator::detect()
+        .map(|elevator| elevator.argv(command))
+        .unwrap_or_default();
+    let action = ResultAction::RunElevated(command.to_string());
+    if !confirm_dangerous(command, &argv, action, app_state, results) {
+        return;
+    }
+
+    match run_elevated(command) {
+        Ok(_) => {
+            app_state.history.borrow_mut().add(command);
+            results.window.close();
+        }
+        Err(e) => {
+            LOG.error(&format!("Failed to run '{}' as root: {}", command, e));
+            results.show_status(&format!("Can't run as root: {}", e));
+        }
+    }
+}
+
+fn terminal_command(command: &str, hold: bool, app_state: &AppState, results: &ResultsArea) {
+    let argv = Terminal::detect()
+        .map(|terminal| terminal.wrap(command, hold))
+        .unwrap_or_default();
+    let action = ResultAction::RunInTerminal(command.to_string());
+    if !confirm_dangerous(command, &argv, action, app_state, results) {
+        return;
+    }
+
+    match run_in_terminal(command, hold) {
+        Ok(_) => {
+            app_state.history.borrow_mut().add(command);
+            results.window.close();
+        }
+        Err(e) => {
+            results.show_status(&format!("Failed to open a terminal: {}", e));
+        }
+    }
+}
+
+fn kill_process(pid: i32, signal: Signal, results: &ResultsArea) {
+    match send_signal(pid, signal) {
+        Ok(_) => results.window.close(),
+        Err(e) => {
+            LOG.error(&format!("Failed to send {:?} to {}: {}", signal, pid, e));
+            results.show_status(&format!("Failed to stop process {}: {}", pid, e));
+        }
+    }
+}
+
+/// open the url in the browser, in a private window if asked to
+fn open_url(url: String, private: bool, app_state: &AppState, results: &ResultsArea) {
+    let manager = app_state.app_manager.clone();
+    let results = results.clone();
+
+    glib::spawn_future_local(async move {
+        // applications are only loaded up front in the default mode
+        let loaded = manager.read().await.count() > 0;
+        if !loaded && let Err(e) = manager.write().await.load_applications().await {
+            LOG.error(&format!("Failed to load applications: {:?}", e));
+        }
+
+        let opened = browser::open_url(&*manager.read().await, &url, private).await;
+        match opened {
+            Ok(_) => {
+                LOG.debug(&format!("Opened URL: {}", url));
+                results.window.close();
+            }
+            Err(e) => {
+                LOG.error(&format!("Failed to open {}: {}", url, e));
+                results.show_status(&format!("Failed to open {}: {}", url, e));
+            }
+        }
+    });
+}
+
+/// icons of the known applications by their program name and window class
+fn app_icons(manager: &ApplicationManager) -> HashMap<String, String> {
+    let mut icons = HashMap::new();
+    for app in manager.get_applications() {
+        let Some(icon) = &app.icon else {
+            continue;
+        };
+        for name in app
+            .program_name()
+            .into_iter()
+            .chain(app.startup_wm_class.as_deref())
+        {
+            icons
+                .entry(name.to_lowercase())
+                .or_insert_with(|| icon.clone());
+        }
+    }
+    icons
+}
+
+/// hold back a dangerous command until the same action is repeated
+///
+/// the first time, the results are replaced by a warning showing exactly what
+/// would be executed, returns whether the command may run now
+fn confirm_dangerous(
+    command: &str,
+    argv: &[String],
+    action: ResultAction,
+    app_state: &AppState,
+    results: &ResultsArea,
+) -> bool {
+    let Some(reason) = check_command(command) else {
+        return true;
+    };
+    if app_state.confirmed_command.borrow().as_deref() == Some(command) {
+        app_state.confirmed_command.replace(None);
+        return true;
+    }
+
+    // searches still running for the query would replace the warning
+    app_state.search.begin();
+    app_state
+        .pending_dangerous
+        .replace(Some((command.to_string(), action)));
+
+    // the row itself does nothing, a second Enter after a paste must not run it
+    let warning = ResultItem::new(&format!("{}: '{}'", reason, command), ResultAction::None)
+        .with_description("Press Ctrl+Y to run it anyway, or change the command")
+        .with_caption(&format!("Runs {}", format_argv(argv)))
+        .with_icon("dialog-warning-symbolic")
+        .with_style("dangerous-command");
+    results.show_items(&[warning], "");
+    false
+}
+
+/// run a command and stream its output into the results
+fn capture_output(command: String, app_state: &Rc<AppState>, results: &ResultsArea) {
+    let action = ResultAction::CaptureCommand(command.clone());
+    if !confirm_dangerous(&command, &shell_argv(&command), action, app_state, results) {
+        return;
+    }
+    app_state.history.borrow_mut().add(&command);
+
+    // a new query or Escape cancels the command through the search token
+    let token = app_state.search.begin();
+    let running = ResultItem::new(&format!("Running '{}'...", command), ResultAction::None)
+        .with_description("Press Escape to stop it")
+        .with_icon("utilities-terminal");
+    results.show_items(std::slice::from_ref(&running), "");
+    app_state.capture_running.set(true);
+
+    let app_state = app_state.clone();
+    let results = results.clone();
+
+    glib::spawn_future_local(async move {
+        let mut output = String::new();
+        let status = capture_command(
+            &command,
+            Duration::from_secs(CONFIG.run.capture_timeout_secs),
+            CONFIG.run.capture_max_bytes,
+            token.cancelled(),
+            |line| {
+                output.push_str(line);
+                output.push('\n');
+                let item = ResultItem::new(line, ResultAction::Copy(line.to_string()))
+                    .with_style("output-line");
+                results.list.append(&item);
+            },
+        )
+        .await;
+        app_state.capture_running.set(false);
+        LOG.debug(&format!("'{}' {}", command, status));
+
+        // the results were replaced by a newer search in the meantime
+        if results.list.item(0).as_ref() != Some(&running) {
+            return;
+        }
+
+        let finished = ResultItem::new(
+            &format!("'{}' {}", command, status),
+            ResultAction::Copy(output),
+        )
+        .with_description("Press Enter to copy the output")
+        .with_icon(match status {
+            CaptureStatus::Exited(0) => "emblem-ok-symbolic",
+            _ => "dialog-warning-symbolic",
+        });
+        let rerun = ResultItem::new("Run again", ResultAction::CaptureCommand(command.clone()))
+            .with_icon("view-refresh-symbolic");
+
+        results.list.replace(0, &[finished, rerun]);
+        results.list.select(0);
+    });
+}
+
+/// run mode row of an executable, described by its man page when it has one
+///
+/// the row runs the line it completes to, the executable with the typed arguments
+fn executable_item(executable: &Executable, description: Option<&str>, line: &str) -> ResultItem {
+    let dir = executable.dir.to_string_lossy();
+    let item = ResultItem::new(
+        &executable.name,
+        ResultAction::RunCommand(line.trim().to_string()),
+    )
+    .with_completion(line);
+
+    match description {
+        Some(description) => item.with_description(description).with_caption(&dir),
+        None => item.with_description(&dir),
+    }
+}
+
+/// command typed in run mode, if the entry is in run mode
+fn run_command_text(search_entry: &Entry, prefix: &str) -> Option<String> {
+    let query = format!("{}{}", prefix, search_entry.text());
+    strip_mode_prefix(&query, RUN_PREFIXES).map(|command| command.to_string())
+}
+
+/// "Open" row for a url or path typed as the whole query
+fn link_items(query: &str, paths: bool) -> Vec<ResultItem> {
+    detect_link(query)
+        .filter(|link| paths || matches!(link, Link::Url(_)))
+        .map(|link| create_link_item(&link))
+        .into_iter()
+        .collect()
+}
+
+/// query typed in web mode
+fn web_query_text(search_entry: &Entry, prefix: &str) -> Option<String> {
+    let query = format!("{}{}", prefix, search_entry.text());
+    strip_mode_prefix(&query, WEB_PREFIXES).map(|query| query.to_string())
+}
+
+/// command line of the selected run mode row, or the typed one
+fn selected_run_command(list: &ResultList, search_entry: &Entry, prefix: &str) -> Option<String> {
+    let typed = run_command_text(search_entry, prefix)?;
+    let command = match list.selected_item().map(|item| item.action()) {
+        Some(ResultAction::RunCommand(command)) => command,
+        _ => typed.trim().to_string(),
+    };
+    (!command.is_empty()).then_some(command)
+}
+
+/// replace the command typed in run mode or the web query, keeping the
+/// part of the mode prefix that was typed into the entry
+fn set_run_command(search_entry: &Entry, prefix: &str, command: &str) {
+    let entry_text = search_entry.text().to_string();
+    let Some(current) =
+        run_command_text(search_entry, prefix).or_else(|| web_query_text(search_entry, prefix))
+    else {
+        return;
+    };
+    let typed_prefix = &entry_text[..entry_text.len() - current.len()];
+
+    search_entry.set_text(&format!("{}{}", typed_prefix, command));
+    search_entry.set_position(-1);
+}
+
+/// put the next older history entry matching the typed command into the entry
+fn recall_history(app_state: &AppState, search_entry: &Entry, prefix: &str) {
+    let Some(command) = run_command_text(search_entry, prefix) else {
+        return;
+    };
+
+    let (base, nth) = match app_state.history_recall.take() {
+        Some((base, nth)) => (base, nth + 1),
+        None => (command.trim().to_string(), 0),
+    };
+
+    let recalled = app_state.history.borrow().recall(&base, nth).cloned();
+    match recalled {
+        Some(recalled) => {
+            // setting the text resets the recall, so store it afterwards
+            set_run_command(search_entry, prefix, &recalled);
+            app_state.history_recall.replace(Some((base, nth)));
+        }
+        None => {
+            // no older match, stay on the last one
+            app_state
+                .history_recall
+                .replace(Some((base, nth.saturating_sub(1))));
+        }
+    }
+}
+
 fn animate_window_height(window: &ApplicationWindow, from: i32, to: i32) {
     let window = window.clone();
     let step = if to > from { 10 } else { -10 };
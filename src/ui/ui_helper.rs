@@ -1,11 +1,21 @@
-use crate::utils::{
-    applications::DesktopApplication,
-    logger::{LogLevel, Logger},
-    web::WebSearchResult,
+use crate::{
+    ui::result_item::{ResultAction, ResultItem},
+    utils::{
+        applications::DesktopApplication,
+        bookmarks::{Bookmark, cached_favicon},
+        browser_history::Visit,
+        completion::{escape_word, expand_path},
+        links::Link,
+        logger::{LogLevel, Logger},
+        processes::{Process, format_memory},
+        scripts::Script,
+        web::WebSearchResult,
+    },
 };
-use gtk::{gdk_pixbuf::PixbufLoader, prelude::*, Box, Label, ListBox, ScrolledWindow};
+use gtk::{Box, Label, gdk_pixbuf::PixbufLoader, prelude::*};
 use lazy_static::lazy_static;
 use rust_embed::Embed;
+use std::{cell::RefCell, collections::HashMap, path::Path};
 
 lazy_static! {
     pub static ref LOG: Logger = Logger::new("ui_helper", LogLevel::Debug);
@@ -13,7 +23,33 @@ lazy_static! {
 #[derive(Embed)]
 #[folder = "resources/icons/"]
 struct Asset;
-fn create_image_from_embedded(data: &[u8]) -> gtk::Image {
+
+thread_local! {
+    // decoded embedded icons, so recycled rows don't decode the same png again
+    static TEXTURE_CACHE: RefCell<HashMap<String, gtk::gdk::Texture>> = RefCell::new(HashMap::new());
+    // icon of each search engine, resolved once instead of for every row
+    static ENGINE_ICONS: RefCell<HashMap<String, EngineIcon>> = RefCell::new(HashMap::new());
+    // theme icon picked for each icon name, so a missing icon is only looked up and logged once
+    static THEME_ICONS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    // icons loaded from files by path, rows are bound again on every scroll and keystroke
+    static FILE_ICONS: RefCell<HashMap<String, Option<gtk::gdk::Texture>>> = RefCell::new(HashMap::new());
+}
+
+/// generic icons of search engines without one, the first in the theme is used
+const ENGINE_FALLBACK_ICONS: &[&str] = &[
+    "web-browser",
+    "web-browser-symbolic",
+    "applications-internet",
+    "globe-symbolic",
+];
+
+#[derive(Clone)]
+enum EngineIcon {
+    Texture(gtk::gdk::Texture),
+    Name(String),
+}
+
+fn create_texture_from_embedded(data: &[u8]) -> gtk::gdk::Texture {
     let loader = PixbufLoader::new();
     loader
         .write(data)
@@ -21,68 +57,267 @@ fn create_image_from_embedded(data: &[u8]) -> gtk::Image {
     loader.close().expect("Failed to close PixbufLoader");
 
     let pixbuf = loader.pixbuf().expect("Failed to get pixbuf from loader");
-    let texture = gtk::gdk::Texture::for_pixbuf(&pixbuf);
+    gtk::gdk::Texture::for_pixbuf(&pixbuf)
+}
+
+/// factory building the recycled row widgets of the result list
+pub fn create_result_factory() -> gtk::SignalListItemFactory {
+    let factory = gtk::SignalListItemFactory::new();
+
+    factory.connect_setup(|_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<gtk::ListItem>()
+            .expect("Needs to be a ListItem");
+
+        let row_box = Box::new(gtk::Orientation::Horizontal, 0);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+        row_box.set_margin_start(8);
+        row_box.set_margin_end(8);
+        row_box.add_css_class("card");
+
+        let icon = gtk::Image::new();
+        icon.set_icon_size(gtk::IconSize::Large);
+        icon.set_margin_start(5);
+
+        let content_box = Box::new(gtk::Orientation::Vertical, 2);
+        content_box.set_margin_top(8);
+        content_box.set_margin_bottom(8);
+        content_box.set_margin_start(8);
+        content_box.set_margin_end(8);
+        content_box.set_hexpand(true);
+
+        let title = Label::new(None);
+        title.set_halign(gtk::Align::Start);
+        title.set_ellipsize(gtk::pango::EllipsizeMode::End);
+
+        let description = Label::new(None);
+        description.set_halign(gtk::Align::Start);
+        description.add_css_class("dim-label");
+        description.set_ellipsize(gtk::pango::EllipsizeMode::End);
+
+        let caption = Label::new(None);
+        caption.set_halign(gtk::Align::Start);
+        caption.add_css_class("dim-label");
+        caption.add_css_class("caption");
+        caption.set_ellipsize(gtk::pango::EllipsizeMode::End);
+
+        content_box.append(&title);
+        content_box.append(&description);
+        content_box.append(&caption);
+
+        row_box.append(&icon);
+        row_box.append(&content_box);
+
+        list_item.set_child(Some(&row_box));
+    });
+
+    factory.connect_bind(|_, list_item| {
+        let list_item = list_item
+            .downcast_ref::<gtk::ListItem>()
+            .expect("Needs to be a ListItem");
+        let Some(item) = list_item.item().and_downcast::<ResultItem>() else {
+            return;
+        };
+        let Some(row_box) = list_item.child().and_downcast::<Box>() else {
+            return;
+        };
+
+        let Some(icon) = row_box.first_child().and_downcast::<gtk::Image>() else {
+            return;
+        };
+        let Some(content_box) = icon.next_sibling().and_downcast::<Box>() else {
+            return;
+        };
+        let labels: Vec<Label> = [
+            content_box.first_child(),
+            content_box.first_child().and_then(|w| w.next_sibling()),
+            content_box.last_child(),
+        ]
+        .into_iter()
+        .filter_map(|w| w.and_downcast::<Label>())
+        .collect();
+        let [title, description, caption] = labels.as_slice() else {
+            return;
+        };
+
+        bind_icon(&icon, &item);
+
+        title.set_text(&item.title());
+        let style = item.style();
+        if style.is_empty() {
+            title.set_css_classes(&["title"]);
+        } else {
+            title.set_css_classes(&[style.as_str()]);
+        }
+
+        for (label, text) in [(description, item.description()), (caption, item.caption())] {
+            label.set_visible(!text.is_empty());
+            label.set_text(&text);
+        }
+    });
 
-    let image = gtk::Image::new();
-    image.set_paintable(Some(&texture));
-    image
+    factory
 }
 
+fn bind_icon(image: &gtk::Image, item: &ResultItem) {
+    image.remove_css_class("dim-icon");
+
+    if let Some(paintable) = item.paintable() {
+        image.set_paintable(Some(&paintable));
+        image.set_visible(true);
+        return;
+    }
+
+    let icon_name = item.icon_name();
+    if icon_name.is_empty() {
+        image.set_visible(false);
+        return;
+    }
+
+    image.set_visible(true);
+    if icon_name.starts_with('/')
+        && let Some(texture) = file_icon(&icon_name)
+    {
+        image.set_paintable(Some(&texture));
+        return;
+    }
+    image.set_icon_name(Some(&resolve_theme_icon(&icon_name)));
+    if !item.style().is_empty() {
+        image.add_css_class("dim-icon");
+    }
+}
 
-pub fn create_app_row(app: &DesktopApplication) -> gtk::ListBoxRow {
-    let row = gtk::ListBoxRow::new();
-    row.set_margin_top(4);
-    row.set_margin_bottom(4);
-    row.set_margin_start(8);
-    row.set_margin_end(8);
+/// icon file decoded once, a file that failed to load isn't tried again
+fn file_icon(path: &str) -> Option<gtk::gdk::Texture> {
+    if let Some(texture) = FILE_ICONS.with(|icons| icons.borrow().get(path).cloned()) {
+        return texture;
+    }
+    let texture = texture_from_file(Path::new(path));
+    FILE_ICONS.with(|icons| icons.borrow_mut().insert(path.to_string(), texture.clone()));
+    texture
+}
+
+/// pick the first icon of the theme matching the name, or the default one
+pub fn resolve_theme_icon(icon_name: &str) -> String {
+    if let Some(icon) = THEME_ICONS.with(|icons| icons.borrow().get(icon_name).cloned()) {
+        return icon;
+    }
+    let Some(display) = gtk::gdk::Display::default() else {
+        return icon_name.to_string();
+    };
+    let icon_theme = gtk::IconTheme::for_display(&display);
+
+    let icon = if icon_theme.has_icon(icon_name) {
+        icon_name.to_string()
+    } else {
+        let fallback_icons = [
+            format!("{}-symbolic", icon_name),
+            icon_name.to_lowercase(),
+            "application-x-executable".to_string(),
+        ];
+        let fallback = fallback_icons
+            .into_iter()
+            .find(|fallback| icon_theme.has_icon(fallback))
+            .unwrap_or_else(|| "application-x-executable".to_string());
+        LOG.warn(&format!(
+            "Failed to get icon for {} falling back to {}",
+            icon_name, fallback
+        ));
+        fallback
+    };
+
+    THEME_ICONS.with(|icons| {
+        icons
+            .borrow_mut()
+            .insert(icon_name.to_string(), icon.clone())
+    });
+    icon
+}
+
+/// row of a user script, run like a typed command
+pub fn create_script_item(script: &Script) -> ResultItem {
+    let command = escape_word(&script.path.to_string_lossy());
+    let action = if script.capture {
+        ResultAction::CaptureCommand(command)
+    } else if script.terminal {
+        ResultAction::RunInTerminal(command)
+    } else {
+        ResultAction::RunCommand(command)
+    };
+
+    ResultItem::new(&script.name, action)
+        .with_description(script.description.as_deref().unwrap_or("Script"))
+        .with_caption(&script.path.to_string_lossy())
+        .with_icon(script.icon.as_deref().unwrap_or("text-x-script"))
+}
+
+/// row of a browser bookmark, with its favicon if one was cached
+pub fn create_bookmark_item(bookmark: &Bookmark) -> ResultItem {
+    let caption = if bookmark.folder.is_empty() {
+        bookmark.browser.clone()
+    } else {
+        format!("{}  ·  {}", bookmark.browser, bookmark.folder)
+    };
+    let icon = bookmark
+        .favicon
+        .as_ref()
+        .map_or("user-bookmarks".to_string(), |path| {
+            path.to_string_lossy().to_string()
+        });
+
+    ResultItem::new(&bookmark.title, ResultAction::OpenUrl(bookmark.url.clone()))
+        .with_description(&bookmark.url)
+        .with_caption(&caption)
+        .with_icon(&icon)
+}
 
-    // Set widget name to app identifier for launch functionality
+/// row of a page from the browser history
+pub fn create_visit_item(visit: &Visit) -> ResultItem {
+    let visits = if visit.visit_count == 1 {
+        "1 visit".to_string()
+    } else {
+        format!("{} visits", visit.visit_count)
+    };
+    let icon = visit
+        .favicon
+        .as_ref()
+        .map_or("document-open-recent".to_string(), |path| {
+            path.to_string_lossy().to_string()
+        });
+
+    ResultItem::new(&visit.title, ResultAction::OpenUrl(visit.url.clone()))
+        .with_description(&visit.url)
+        .with_caption(&format!(
+            "{}  ·  {}  ·  {}",
+            visit.browser,
+            visit.last_visit_label(),
+            visits
+        ))
+        .with_icon(&icon)
+}
+
+/// row of a running process, with the icon of its application if known
+pub fn create_process_item(process: &Process, icon: Option<&str>) -> ResultItem {
+    ResultItem::new(&process.name, ResultAction::KillProcess(process.pid))
+        .with_description(&format!(
+            "PID {}  ·  CPU {:.1}%  ·  {}",
+            process.pid,
+            process.cpu_percent,
+            format_memory(process.memory_bytes)
+        ))
+        .with_caption(&process.command_line)
+        .with_icon(icon.unwrap_or("application-x-executable"))
+}
+
+pub fn create_app_item(app: &DesktopApplication) -> ResultItem {
+    // app identifier used for the launch functionality
     let app_id = app
         .desktop_file_path
         .file_stem()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
-    row.set_widget_name(&app_id);
-
-    let row_box = Box::new(gtk::Orientation::Horizontal, 0);
-    row_box.set_margin_top(8);
-    row_box.set_margin_bottom(8);
-    row_box.set_margin_start(8);
-    row_box.set_margin_end(8);
-
-    // app icon
-    let icon = if let Some(icon_name) = &app.icon {
-        if icon_name.starts_with('/') {
-            let image = gtk::Image::new();
-            image.set_from_file(Some(icon_name));
-            if image.paintable().is_some() {
-                image.set_icon_size(gtk::IconSize::Large);
-                image.set_margin_start(5);
-                image
-            } else {
-                create_icon_from_theme(icon_name)
-            }
-        } else {
-            create_icon_from_theme(icon_name)
-        }
-    } else {
-        LOG.warn(&format!("Failed to get icon"));
-        LOG.warn(&format!("Falling back to default"));
-        create_default_icon()
-    };
-
-    // app name and description
-    let app_box = Box::new(gtk::Orientation::Vertical, 2);
-    app_box.set_margin_top(8);
-    app_box.set_margin_bottom(8);
-    app_box.set_margin_start(8);
-    app_box.set_margin_end(8);
-    app_box.set_hexpand(true);
-
-    let app_title = Label::new(Some(&app.name));
-    app_title.set_halign(gtk::Align::Start);
-    app_title.add_css_class("title");
 
     // use app comments or GenericName as description
     let description = app
@@ -92,191 +327,158 @@ pub fn create_app_row(app: &DesktopApplication) -> gtk::ListBoxRow {
         .map(|s| s.as_str())
         .unwrap_or("Application");
 
-    let app_desc = Label::new(Some(description));
-    app_desc.set_halign(gtk::Align::Start);
-    app_desc.add_css_class("dim-label");
-    app_desc.set_ellipsize(gtk::pango::EllipsizeMode::End);
-
-    app_box.append(&app_title);
-    app_box.append(&app_desc);
-
-    if !app.categories.is_empty() {
-        let categories_text = app.categories.join(", ");
-        let categories_label = Label::new(Some(&categories_text));
-        categories_label.set_halign(gtk::Align::Start);
-        categories_label.add_css_class("dim-label");
-        categories_label.add_css_class("caption");
-        categories_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-        app_box.append(&categories_label);
-    }
+    let icon = app.icon.as_deref().unwrap_or_else(|| {
+        LOG.warn(&format!(
+            "Failed to get icon for {}, falling back to default",
+            app.name
+        ));
+        "application-x-executable"
+    });
+
+    ResultItem::new(&app.name, ResultAction::Launch(app_id))
+        .with_description(description)
+        .with_caption(&app.categories.join(", "))
+        .with_icon(icon)
+}
 
-    row_box.append(&icon);
-    row_box.append(&app_box);
+pub fn create_web_search_item(result: &WebSearchResult) -> ResultItem {
+    let item = ResultItem::new(&result.title, ResultAction::OpenUrl(result.url.clone()))
+        .with_description(&result.description)
+        .with_caption(&result.url);
 
-    row.set_child(Some(&row_box));
-    row.add_css_class("card");
-    row.set_activatable(true);
+    match search_engine_icon(result) {
+        EngineIcon::Texture(texture) => {
+            item.set_paintable(Some(texture.upcast_ref::<gtk::gdk::Paintable>()))
+        }
+        EngineIcon::Name(name) => item.set_icon_name(name.as_str()),
+    }
 
-    row
+    item
 }
 
-pub fn create_icon_from_theme(icon_name: &str) -> gtk::Image {
-    let image = gtk::Image::new();
+/// row opening a typed url or path directly
+pub fn create_link_item(link: &Link) -> ResultItem {
+    let target = link.target();
+    let title = format!("Open {}", target);
+    match link {
+        Link::Url(url) => ResultItem::new(&title, ResultAction::OpenUrl(url.clone()))
+            .with_description("Open in the browser")
+            .with_icon("web-browser"),
+        Link::Path(path) => {
+            let (content_type, _) = gtk::gio::content_type_guess(Some(path), &[]);
+            let icon = if path.is_dir() {
+                "folder".to_string()
+            } else {
+                gtk::gio::content_type_get_generic_icon_name(&content_type)
+                    .map(|icon| icon.to_string())
+                    .unwrap_or_else(|| "text-x-generic".to_string())
+            };
+            ResultItem::new(&title, ResultAction::OpenPath(target))
+                .with_description("Open with the default application")
+                .with_icon(&icon)
+        }
+    }
+}
 
-    if let Some(display) = gtk::gdk::Display::default() {
-        let icon_theme = gtk::IconTheme::for_display(&display);
+/// row of a search engine suggestion, Tab puts it into the entry
+pub fn create_suggestion_item(result: &WebSearchResult) -> ResultItem {
+    create_web_search_item(result)
+        .with_style("web-suggestion")
+        .with_completion(&result.title)
+}
 
-        if icon_theme.has_icon(icon_name) {
-            image.set_icon_name(Some(icon_name));
-            image.set_icon_size(gtk::IconSize::Large);
-            image.set_margin_start(5);
-            return image;
-        }
+/// icon of the engine of the result, the first found of the configured file or
+/// theme icon, the embedded icon of a built-in engine, the cached favicon of
+/// its site and a generic icon
+fn search_engine_icon(result: &WebSearchResult) -> EngineIcon {
+    let engine = &result.search_engine;
+    if let Some(icon) = ENGINE_ICONS.with(|icons| icons.borrow().get(engine).cloned()) {
+        return icon;
     }
 
-    let fallback_icons = [
-        icon_name,
-        &format!("{}-symbolic", icon_name),
-        &icon_name.to_lowercase(),
-        "application-x-executable",
-    ];
-
-    for fallback in &fallback_icons {
-        if let Some(display) = gtk::gdk::Display::default() {
-            let icon_theme = gtk::IconTheme::for_display(&display);
-            if icon_theme.has_icon(fallback) {
-                image.set_icon_name(Some(fallback));
-                image.set_icon_size(gtk::IconSize::Large);
-                image.set_margin_start(5);
-                return image;
-            } else {
-                LOG.warn(&format!(
-                    "Failed to get icon for {} falling back to default {}",
-                    icon_name, fallback
-                ));
-            }
+    let icon = result
+        .icon
+        .as_deref()
+        .and_then(configured_engine_icon)
+        .or_else(|| search_engine_texture(engine).map(EngineIcon::Texture))
+        .or_else(|| {
+            cached_favicon(&result.url)
+                .and_then(|favicon| texture_from_file(&favicon))
+                .map(EngineIcon::Texture)
+        })
+        .unwrap_or_else(fallback_engine_icon);
+    ENGINE_ICONS.with(|icons| icons.borrow_mut().insert(engine.clone(), icon.clone()));
+    icon
+}
+
+/// icon set in the engine config, a file path or a theme icon name
+fn configured_engine_icon(icon: &str) -> Option<EngineIcon> {
+    if icon.contains('/') {
+        let path = expand_path(icon);
+        let texture = texture_from_file(&path);
+        if texture.is_none() {
+            LOG.warn(&format!("Failed to load engine icon {}", path.display()));
         }
+        return texture.map(EngineIcon::Texture);
     }
 
-    create_default_icon()
+    let icon_theme = gtk::IconTheme::for_display(&gtk::gdk::Display::default()?);
+    if icon_theme.has_icon(icon) {
+        Some(EngineIcon::Name(icon.to_string()))
+    } else {
+        LOG.warn(&format!("engine icon {} is not in the icon theme", icon));
+        None
+    }
 }
 
-fn create_default_icon() -> gtk::Image {
-    let image = gtk::Image::from_icon_name("application-x-executable");
-    image.set_icon_size(gtk::IconSize::Large);
-    image.set_margin_start(5);
-    image
+fn fallback_engine_icon() -> EngineIcon {
+    let icon = gtk::gdk::Display::default()
+        .map(|display| gtk::IconTheme::for_display(&display))
+        .and_then(|icon_theme| {
+            ENGINE_FALLBACK_ICONS
+                .iter()
+                .find(|icon| icon_theme.has_icon(icon))
+        })
+        .copied()
+        // the default of `resolve_theme_icon`, so rows don't log a fallback
+        .unwrap_or("application-x-executable");
+    EngineIcon::Name(icon.to_string())
 }
 
-pub fn create_web_search_row(result: &WebSearchResult, _query: &str) -> gtk::ListBoxRow {
-    let row = gtk::ListBoxRow::new();
-    row.set_margin_bottom(4);
-    row.set_margin_bottom(4);
-    row.set_margin_start(8);
-    row.set_margin_end(8);
-
-    row.set_widget_name(&result.url);
-
-    let row_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-    row_box.set_margin_top(8);
-    row_box.set_margin_bottom(8);
-    row_box.set_margin_start(8);
-    row_box.set_margin_end(8);
-
-    let icon = create_search_engine_icon(&result.search_engine);
-
-    let content_box = gtk::Box::new(gtk::Orientation::Vertical, 2);
-    content_box.set_margin_top(8);
-    content_box.set_margin_bottom(8);
-    content_box.set_margin_start(8);
-    content_box.set_margin_end(8);
-    content_box.set_hexpand(true);
-
-    let title_label = gtk::Label::new(Some(&result.title));
-    title_label.set_halign(gtk::Align::Start);
-    title_label.add_css_class("title");
-
-    let desc_label = gtk::Label::new(Some(&result.description));
-    desc_label.set_halign(gtk::Align::Start);
-    desc_label.add_css_class("dim-label");
-    desc_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-
-    let url_label = gtk::Label::new(Some(&result.url));
-    url_label.set_halign(gtk::Align::Start);
-    url_label.add_css_class("dim-label");
-    url_label.add_css_class("caption");
-    url_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-
-    content_box.append(&title_label);
-    content_box.append(&desc_label);
-    content_box.append(&url_label);
-
-    row_box.append(&icon);
-    row_box.append(&content_box);
-
-    row.set_child(Some(&row_box));
-    row.add_css_class("card");
-    row.set_activatable(true);
-
-    row
+fn texture_from_file(path: &Path) -> Option<gtk::gdk::Texture> {
+    gtk::gdk::Texture::from_filename(path).ok()
 }
 
-pub fn create_search_engine_icon(engine: &str) -> gtk::Image {
-    let image = gtk::Image::new();
-
+/// embedded icon of a built-in engine
+fn search_engine_texture(engine: &str) -> Option<gtk::gdk::Texture> {
     let icon_name = match engine {
         "google" => "google.png",
         "duckduckgo" => "duckduckgo.png",
         "youtube" => "youtube.png",
         "stackoverflow" => "stackoverflow.png",
-        _ => {
-            LOG.debug("how there can be another engine ??");
-            image.set_icon_name(Some("web-browser"));
-            image.set_icon_size(gtk::IconSize::Large);
-            image.set_margin_start(5);
-            return image;
-        }
+        _ => return None,
     };
+
+    if let Some(texture) = TEXTURE_CACHE.with(|cache| cache.borrow().get(icon_name).cloned()) {
+        return Some(texture);
+    }
+
     match Asset::get(icon_name) {
         Some(content) => {
-            let image = create_image_from_embedded(&content.data);
-            image.set_icon_size(gtk::IconSize::Large);
-            image.set_margin_start(5);
-            image
+            let texture = create_texture_from_embedded(&content.data);
+            TEXTURE_CACHE.with(|cache| {
+                cache
+                    .borrow_mut()
+                    .insert(icon_name.to_string(), texture.clone())
+            });
+            Some(texture)
         }
         None => {
-            eprintln!(
-                "Warning: embedded icon {} missing, falling back to default icon",
+            LOG.warn(&format!(
+                "embedded icon {} missing, falling back to default icon",
                 icon_name
-            );
-            let image = gtk::Image::new();
-            image.set_icon_name(Some("web-browser"));
-            image.set_icon_size(gtk::IconSize::Large);
-            image.set_margin_start(5);
-            image
-        }
-    }
-}
-
-pub fn scroll_to_selected(list_box: &ListBox, scrolled_window: &ScrolledWindow) {
-    if let Some(selected_row) = list_box.selected_row() {
-        if let Some((_x, y, _width, height)) = selected_row.bounds() {
-            let row_top = y as f64;
-            let row_bottom = (y + height) as f64;
-
-            let adjustment = scrolled_window.vadjustment();
-            let visible_top = adjustment.value();
-            let visible_bottom = visible_top + adjustment.page_size();
-            let padding = 50.0;
-
-            if row_top < visible_top + padding {
-                adjustment.set_value((row_top - padding).max(0.0));
-            } else if row_bottom > visible_bottom - padding {
-                adjustment.set_value(
-                    (row_bottom - adjustment.page_size() + padding)
-                        .min(adjustment.upper() - adjustment.page_size()),
-                );
-            }
+            ));
+            None
         }
     }
 }
@@ -0,0 +1,241 @@
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    shell::{login_env, shell_program, write_private_file},
+};
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("aliases", LogLevel::Debug);
+}
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+// separates whatever the shell config prints from the definitions themselves
+const DEFINITIONS_MARKER: &str = "__STARLIGHT_DEFINITIONS__";
+const MAX_EXPANSION_LENGTH: usize = 120;
+
+// every script prints `kind\0name\0expansion\0` records after the marker
+const BASH_SCRIPT: &str = r#"
+printf '\0%s\0' "$1"
+for name in "${!BASH_ALIASES[@]}"; do
+    printf 'alias\0%s\0%s\0' "$name" "${BASH_ALIASES[$name]}"
+done
+for name in $(compgen -A function); do
+    printf 'function\0%s\0%s\0' "$name" "$(declare -f "$name" | tail -n +3 | head -n -1)"
+done
+"#;
+
+const ZSH_SCRIPT: &str = r#"
+printf '\0%s\0' "$1"
+for name value in "${(@kv)aliases}"; do
+    printf 'alias\0%s\0%s\0' "$name" "$value"
+done
+for name value in "${(@kv)functions}"; do
+    printf 'function\0%s\0%s\0' "$name" "$value"
+done
+"#;
+
+// fish aliases are functions described as `alias name=expansion`
+const FISH_SCRIPT: &str = r#"
+printf '\0%s\0' $argv[1]
+for name in (functions --names)
+    printf 'function\0%s\0%s\0' $name (functions --details --verbose $name)[5]
+end
+"#;
+
+/// aliases and functions of the user's shell, filled in at startup
+static DEFINITIONS: Lazy<RwLock<Vec<ShellDefinition>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefinitionKind {
+    Alias,
+    Function,
+}
+
+impl DefinitionKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DefinitionKind::Alias => "Shell alias",
+            DefinitionKind::Function => "Shell function",
+        }
+    }
+}
+
+/// alias or function defined in the user's shell config
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellDefinition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    /// what the alias expands to, or the body of the function on one line
+    pub expansion: String,
+}
+
+/// definitions whose name contains the query
+pub fn search(query: &str) -> Vec<ShellDefinition> {
+    DEFINITIONS
+        .read()
+        .map(|definitions| {
+            definitions
+                .iter()
+                .filter(|definition| definition.name.contains(query))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// whether the name is an alias or function of the user's shell
+pub fn is_defined(name: &str) -> bool {
+    DEFINITIONS
+        .read()
+        .map(|definitions| definitions.iter().any(|definition| definition.name == name))
+        .unwrap_or(false)
+}
+
+fn set_definitions(definitions: Vec<ShellDefinition>) {
+    if let Ok(mut current) = DEFINITIONS.write() {
+        *current = definitions;
+    }
+}
+
+fn definitions_cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("starlight")
+        .join("shell-definitions")
+}
+
+/// load the definitions cached at the last start, then ask the shell again
+///
+/// interactive shells can take a while to start, so the cached copy is
+/// what run mode shows until the shell answers
+pub async fn refresh() {
+    if let Ok(content) = std::fs::read_to_string(definitions_cache_path()) {
+        set_definitions(parse_definitions(&content));
+    }
+
+    let Some(content) = query_shell().await else {
+        return;
+    };
+    let definitions = parse_definitions(&content);
+    LOG.debug(&format!(
+        "found {} aliases and functions",
+        definitions.len()
+    ));
+    set_definitions(definitions);
+
+    let path = definitions_cache_path();
+    if let Err(e) = write_private_file(&path, &content) {
+        LOG.error(&format!(
+            "Failed to cache shell definitions to {}: {}",
+            path.display(),
+            e
+        ));
+    }
+}
+
+/// raw definition records printed by an interactive shell
+async fn query_shell() -> Option<String> {
+    let shell = shell_program();
+    let name = shell.rsplit('/').next().unwrap_or(&shell);
+    let script = match name {
+        "bash" => BASH_SCRIPT,
+        "zsh" => ZSH_SCRIPT,
+        "fish" => FISH_SCRIPT,
+        _ => {
+            LOG.debug(&format!("can't list aliases of {}", shell));
+            return None;
+        }
+    };
+
+    // fish passes the arguments after the script as $argv, the others start with $0
+    let mut args = vec!["-i", "-c", script];
+    if name != "fish" {
+        args.push(name);
+    }
+    args.push(DEFINITIONS_MARKER);
+
+    let mut cmd = tokio::process::Command::new(&shell);
+    if let Some(env) = login_env() {
+        cmd.envs(env);
+    }
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    let output = match tokio::time::timeout(QUERY_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            LOG.error(&format!("Failed to list aliases of {}: {}", shell, e));
+            return None;
+        }
+        Err(_) => {
+            LOG.warn(&format!("listing aliases of {} timed out", shell));
+            return None;
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let marker = format!("\0{}\0", DEFINITIONS_MARKER);
+    let (_, records) = stdout.split_once(&marker)?;
+    Some(records.to_string())
+}
+
+/// parse `kind\0name\0expansion\0` records
+fn parse_definitions(content: &str) -> Vec<ShellDefinition> {
+    let fields = content.split('\0').collect::<Vec<_>>();
+    let mut definitions = fields
+        .chunks_exact(3)
+        .filter_map(|record| {
+            let (kind, name, expansion) = (record[0], record[1], record[2]);
+            // helpers of completion scripts and prompts aren't meant to be run
+            if name.is_empty() || name.starts_with('_') || name.starts_with("fish_") {
+                return None;
+            }
+
+            let (kind, expansion) = match kind {
+                "alias" => (DefinitionKind::Alias, expansion),
+                "function" => match expansion
+                    .strip_prefix("alias ")
+                    .and_then(|alias| alias.strip_prefix(name))
+                    .and_then(|alias| alias.strip_prefix('='))
+                {
+                    Some(alias) => (DefinitionKind::Alias, alias),
+                    None => (DefinitionKind::Function, expansion),
+                },
+                _ => return None,
+            };
+
+            Some(ShellDefinition {
+                name: name.to_string(),
+                kind,
+                expansion: single_line(expansion),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    definitions.sort_by(|a, b| a.name.cmp(&b.name));
+    definitions.dedup_by(|a, b| a.name == b.name);
+    definitions
+}
+
+/// squash a function body onto one short line
+fn single_line(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(|line| line.trim().trim_end_matches(';'))
+        .filter(|line| !line.is_empty() && *line != "{" && *line != "}")
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    if line.chars().count() > MAX_EXPANSION_LENGTH {
+        let short = line.chars().take(MAX_EXPANSION_LENGTH).collect::<String>();
+        format!("{}…", short.trim_end())
+    } else {
+        line
+    }
+}
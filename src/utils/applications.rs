@@ -4,7 +4,9 @@ use serde::{Deserialize, Serialize};
 use tokio::fs as async_fs;
 use tokio::task;
 use futures::future::join_all;
+use crate::utils::completion::escape_word;
 use crate::utils::logger::{LogLevel, Logger};
+use crate::utils::terminal::Terminal;
 
 lazy_static::lazy_static! {
     static ref LOG: Logger = Logger::new("applications", LogLevel::Debug);
@@ -55,6 +57,17 @@ impl Default for DesktopApplication {
     }
 }
 
+impl DesktopApplication {
+    /// file name of the program the Exec line starts, skipping `env VAR=value`
+    pub fn program_name(&self) -> Option<&str> {
+        self.exec
+            .split_whitespace()
+            .find(|word| *word != "env" && !word.contains('='))
+            .map(|program| program.trim_matches('"'))
+            .map(|program| program.rsplit('/').next().unwrap_or(program))
+    }
+}
+
 #[derive(Debug)]
 pub enum ApplicationError {
     IoError(std::io::Error),
@@ -62,6 +75,16 @@ pub enum ApplicationError {
     InvalidDesktopFile(String),
 }
 
+impl std::fmt::Display for ApplicationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApplicationError::IoError(e) => write!(f, "{}", e),
+            ApplicationError::ParseError(message) => write!(f, "{}", message),
+            ApplicationError::InvalidDesktopFile(message) => write!(f, "invalid desktop file: {}", message),
+        }
+    }
+}
+
 impl From<std::io::Error> for ApplicationError {
     fn from(err: std::io::Error) -> Self {
         ApplicationError::IoError(err)
@@ -306,9 +329,22 @@ impl ApplicationManager {
 
     /// launch an application
     pub async fn launch_application(&self, app: &DesktopApplication) -> Result<(), ApplicationError> {
+        self.launch_application_with(app, &[]).await
+    }
+
+    /// launch an application with urls or files to open
+    ///
+    /// the arguments take the place of the `%u`, `%U`, `%f` or `%F` field code,
+    /// or get appended when the Exec line has none
+    pub async fn launch_application_with(&self, app: &DesktopApplication, args: &[String]) -> Result<(), ApplicationError> {
         LOG.debug(&format!("Launching application: {}", app.name));
         
-        let mut command = self.parse_exec_command(&app.exec);
+        let argv = Self::exec_argv(&app.exec, args);
+        let Some((program, program_args)) = argv.split_first() else {
+            return Err(ApplicationError::ParseError(format!("empty Exec line for {}", app.name)));
+        };
+        let mut command = tokio::process::Command::new(program);
+        command.args(program_args);
         
         // Set working directory if specified
         if let Some(path) = &app.path {
@@ -317,24 +353,28 @@ impl ApplicationManager {
         
         // handle terminal applications
         if app.terminal {
-
-            // try to find a terminal emulator to launch terminal apps
-            let terminal_emulators = ["kitty", "alacritty", "gnome-terminal", "konsole", "foot"];
-            let mut terminal_cmd = None;
-            
-            for terminal in &terminal_emulators {
-                if Self::command_exists(terminal) {
-                    terminal_cmd = Some(*terminal);
-                    break;
+            if let Some(terminal) = Terminal::detect() {
+                let mut line = Self::clean_exec(&app.exec);
+                for arg in args {
+                    line.push(' ');
+                    line.push_str(&escape_word(arg));
                 }
-            }
-            
-            if let Some(terminal) = terminal_cmd {
-                command = tokio::process::Command::new(terminal);
-                command.args(&["-e", &app.exec]);
+                let argv = terminal.wrap(&line, false);
+                command = tokio::process::Command::new(&argv[0]);
+                command.args(&argv[1..]);
+                if let Some(path) = &app.path {
+                    command.current_dir(path);
+                }
+            } else {
+                LOG.warn(&format!("No terminal found for {}", app.name));
             }
         }
         
+        Self::spawn_detached(command, &app.name)
+    }
+
+    /// spawn a program without waiting for it, the child is reaped once it exits
+    pub fn spawn_detached(mut command: tokio::process::Command, name: &str) -> Result<(), ApplicationError> {
         let result = command.spawn();
         
         match result {
@@ -347,14 +387,15 @@ impl ApplicationManager {
                 Ok(())
             }
             Err(e) => {
-                LOG.error(&format!("Failed to launch {}: {}", app.name, e));
+                LOG.error(&format!("Failed to launch {}: {}", name, e));
                 Err(ApplicationError::IoError(e))
             }
         }
     }
 
-    fn parse_exec_command(&self, exec: &str) -> tokio::process::Command {
-        let cleaned_exec = exec
+    /// strip the field codes from a desktop entry Exec line
+    fn clean_exec(exec: &str) -> String {
+        exec
             .replace("%f", "")  // single file
             .replace("%F", "")  // multiple files  
             .replace("%u", "")  // single URL
@@ -367,16 +408,33 @@ impl ApplicationManager {
             .replace("%c", "")  // translated name
             .replace("%k", "")  // desktop file location
             .replace("%v", "")  // device
-            .replace("%%", "%"); // literal %
-        
-        let parts: Vec<&str> = cleaned_exec.split_whitespace().collect();
-        let mut command = tokio::process::Command::new(parts[0]);
+            .replace("%%", "%") // literal %
+    }
+
+    /// split an Exec line into its arguments, putting the given ones in place
+    /// of the first url or file field code
+    fn exec_argv(exec: &str, args: &[String]) -> Vec<String> {
+        let mut argv = Vec::new();
+        let mut inserted = false;
         
-        if parts.len() > 1 {
-            command.args(&parts[1..]);
+        for part in exec.split_whitespace() {
+            if matches!(part, "%u" | "%U" | "%f" | "%F") {
+                if !inserted {
+                    argv.extend(args.iter().cloned());
+                    inserted = true;
+                }
+                continue;
+            }
+            let part = Self::clean_exec(part);
+            if !part.is_empty() {
+                argv.push(part);
+            }
         }
         
-        command
+        if !inserted {
+            argv.extend(args.iter().cloned());
+        }
+        argv
     }
 
     /// refresh applications from disk
@@ -0,0 +1,564 @@
+use crate::utils::logger::{LogLevel, Logger};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::{HashMap, HashSet};
+use std::fs::{DirBuilder, File, OpenOptions};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("bookmarks", LogLevel::Debug);
+}
+
+// bookmark rows shown for one query
+const MAX_RESULTS: usize = 20;
+
+/// firefox based browsers, by their profiles directory below the home directory
+const FIREFOX_DIRS: &[(&str, &str)] = &[
+    ("Firefox", ".mozilla/firefox"),
+    ("Firefox", "snap/firefox/common/.mozilla/firefox"),
+    ("Firefox", ".var/app/org.mozilla.firefox/.mozilla/firefox"),
+    ("LibreWolf", ".librewolf"),
+    ("Waterfox", ".waterfox"),
+    ("Floorp", ".floorp"),
+    ("Zen", ".zen"),
+];
+
+/// chromium based browsers, by their user data directory below `~/.config`
+const CHROMIUM_DIRS: &[(&str, &str)] = &[
+    ("Chromium", "chromium"),
+    ("Chrome", "google-chrome"),
+    ("Chrome Beta", "google-chrome-beta"),
+    ("Brave", "BraveSoftware/Brave-Browser"),
+    ("Vivaldi", "vivaldi"),
+    ("Edge", "microsoft-edge"),
+    ("Opera", "opera"),
+];
+
+/// names of the firefox root folders by their guid
+const FIREFOX_ROOTS: &[(&str, &str)] = &[
+    ("menu________", "Bookmarks Menu"),
+    ("toolbar_____", "Bookmarks Toolbar"),
+    ("unfiled_____", "Other Bookmarks"),
+    ("mobile______", "Mobile Bookmarks"),
+];
+
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+    /// folders from the root down to the bookmark, joined by `/`
+    pub folder: String,
+    /// browser the bookmark comes from
+    pub browser: String,
+    /// cached favicon file
+    pub favicon: Option<PathBuf>,
+}
+
+impl Bookmark {
+    fn score(&self, words: &[String]) -> Option<u32> {
+        match_score(words, &self.title, &self.url, &self.folder)
+    }
+}
+
+/// how well a page matches all query words, `None` if it doesn't
+///
+/// words in the title count more than in the url, the folder counts least
+pub fn match_score(words: &[String], title: &str, url: &str, folder: &str) -> Option<u32> {
+    let title = title.to_lowercase();
+    let url = url.to_lowercase();
+    let folder = folder.to_lowercase();
+
+    words.iter().try_fold(0, |score, word| {
+        let word_score = if title.starts_with(word.as_str()) {
+            4
+        } else if title.contains(word.as_str()) {
+            3
+        } else if url.contains(word.as_str()) {
+            2
+        } else if folder.contains(word.as_str()) {
+            1
+        } else {
+            return None;
+        };
+        Some(score + word_score)
+    })
+}
+
+/// bookmarks of all detected firefox and chromium profiles
+///
+/// the same url bookmarked in several profiles is listed once
+pub fn load_bookmarks() -> Vec<Bookmark> {
+    let mut bookmarks = Vec::new();
+    for (browser, profile) in firefox_profiles() {
+        match read_firefox(&profile, browser) {
+            Ok(found) => bookmarks.extend(found),
+            Err(e) => LOG.warn(&format!(
+                "Failed to read bookmarks of {}: {}",
+                profile.display(),
+                e
+            )),
+        }
+    }
+    for (browser, profile) in chromium_profiles() {
+        bookmarks.extend(read_chromium(&profile, browser));
+    }
+
+    let mut seen = HashSet::new();
+    bookmarks.retain(|bookmark| seen.insert(bookmark.url.clone()));
+
+    LOG.debug(&format!("loaded {} bookmarks", bookmarks.len()));
+    bookmarks
+}
+
+/// bookmarks matching every word of the query, best matches first
+pub fn search_bookmarks<'a>(bookmarks: &'a [Bookmark], query: &str) -> Vec<&'a Bookmark> {
+    let words = query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = bookmarks
+        .iter()
+        .filter_map(|bookmark| Some((bookmark.score(&words)?, bookmark)))
+        .collect::<Vec<_>>();
+    // stable, so equally good bookmarks keep their order
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, bookmark)| bookmark)
+        .collect()
+}
+
+/// profiles of firefox based browsers with the name of the browser
+pub fn firefox_profiles() -> Vec<(&'static str, PathBuf)> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    FIREFOX_DIRS
+        .iter()
+        .flat_map(|(browser, dir)| {
+            profiles(&home.join(dir), "places.sqlite")
+                .into_iter()
+                .map(move |profile| (*browser, profile))
+        })
+        .collect()
+}
+
+/// profiles of chromium based browsers with the name of the browser
+pub fn chromium_profiles() -> Vec<(&'static str, PathBuf)> {
+    let Some(config) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    CHROMIUM_DIRS
+        .iter()
+        .flat_map(|(browser, dir)| {
+            profiles(&config.join(dir), "Preferences")
+                .into_iter()
+                .map(move |profile| (*browser, profile))
+        })
+        .collect()
+}
+
+fn favicons_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("starlight")
+        .join("favicons")
+}
+
+/// profile directories below the browser directory holding the file
+fn profiles(dir: &Path, file: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(file).is_file())
+        .collect()
+}
+
+/// copy of a browser database in a private directory, the browser keeps
+/// the original locked
+///
+/// the write-ahead log is copied along, it holds the latest changes,
+/// the files are removed once the copy is dropped
+pub struct DatabaseCopy {
+    connection: Option<Connection>,
+    path: PathBuf,
+}
+
+impl DatabaseCopy {
+    pub fn open(database: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = database_copies_dir();
+        DirBuilder::new().recursive(true).mode(0o700).create(&dir)?;
+        let path = dir.join(format!(
+            "database-{}-{}.sqlite",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let mut copy = Self {
+            connection: None,
+            path,
+        };
+
+        copy_private(database, &copy.path)?;
+        let wal = wal_path(database);
+        if wal.is_file() {
+            copy_private(&wal, &wal_path(&copy.path))?;
+        }
+        copy.connection = Some(Connection::open_with_flags(
+            &copy.path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE,
+        )?);
+        Ok(copy)
+    }
+
+    pub fn connection(&self) -> &Connection {
+        self.connection
+            .as_ref()
+            .expect("connection is open until drop")
+    }
+}
+
+impl Drop for DatabaseCopy {
+    fn drop(&mut self) {
+        // close first, sqlite may still write to the side files
+        self.connection.take();
+        for path in [
+            self.path.clone(),
+            wal_path(&self.path),
+            self.path.with_extension("sqlite-shm"),
+        ] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// runtime directory, or the cache directory when there is none
+fn database_copies_dir() -> PathBuf {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .unwrap_or_default()
+        .join("starlight")
+}
+
+/// copy into a new file only we can read, never through an existing file or symlink
+fn copy_private(from: &Path, to: &Path) -> std::io::Result<()> {
+    let mut source = File::open(from)?;
+    let mut target = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(to)?;
+    std::io::copy(&mut source, &mut target)?;
+    Ok(())
+}
+
+fn wal_path(database: &Path) -> PathBuf {
+    let mut path = database.as_os_str().to_owned();
+    path.push("-wal");
+    PathBuf::from(path)
+}
+
+fn read_firefox(
+    profile: &Path,
+    browser: &str,
+) -> Result<Vec<Bookmark>, Box<dyn std::error::Error>> {
+    let places = DatabaseCopy::open(&profile.join("places.sqlite"))?;
+    let connection = places.connection();
+
+    // folders with their parent, to build the full folder path
+    let mut folders = HashMap::new();
+    let mut statement =
+        connection.prepare("SELECT id, parent, title, guid FROM moz_bookmarks WHERE type = 2")?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    for (id, parent, title, guid) in rows.flatten() {
+        let title = FIREFOX_ROOTS
+            .iter()
+            .find(|(root, _)| *root == guid)
+            .map_or(title, |(_, name)| name.to_string());
+        folders.insert(id, (parent, title));
+    }
+
+    let mut statement = connection.prepare(
+        "SELECT b.title, p.url, b.parent FROM moz_bookmarks b
+         JOIN moz_places p ON b.fk = p.id
+         WHERE b.type = 1 AND p.url NOT LIKE 'place:%'",
+    )?;
+    let mut bookmarks = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?
+        .flatten()
+        .map(|(title, url, parent)| Bookmark {
+            title: title
+                .filter(|title| !title.is_empty())
+                .unwrap_or(url.clone()),
+            folder: folder_path(&folders, parent),
+            browser: browser.to_string(),
+            url,
+            favicon: None,
+        })
+        .collect::<Vec<_>>();
+
+    set_favicons(profile, &mut bookmarks);
+    Ok(bookmarks)
+}
+
+/// folder titles from the root down to the folder
+fn folder_path(folders: &HashMap<i64, (i64, String)>, mut id: i64) -> String {
+    let mut path = Vec::new();
+    // the depth limit guards against a corrupt, cyclic tree
+    while let Some((parent, title)) = folders.get(&id)
+        && path.len() < 32
+    {
+        if !title.is_empty() {
+            path.push(title.as_str());
+        }
+        id = *parent;
+    }
+    path.reverse();
+    path.join("/")
+}
+
+fn read_chromium(profile: &Path, browser: &str) -> Vec<Bookmark> {
+    let file = profile.join("Bookmarks");
+    if !file.is_file() {
+        return Vec::new();
+    }
+    let json = match std::fs::read_to_string(&file)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string())
+        }) {
+        Ok(json) => json,
+        Err(e) => {
+            LOG.warn(&format!("Failed to read {}: {}", file.display(), e));
+            return Vec::new();
+        }
+    };
+
+    let mut bookmarks = Vec::new();
+    if let Some(roots) = json.get("roots").and_then(|roots| roots.as_object()) {
+        for root in roots.values() {
+            collect_chromium(root, "", browser, &mut bookmarks);
+        }
+    }
+
+    set_favicons(profile, &mut bookmarks);
+    bookmarks
+}
+
+/// walk a node of the chromium bookmark tree
+fn collect_chromium(
+    node: &serde_json::Value,
+    folder: &str,
+    browser: &str,
+    bookmarks: &mut Vec<Bookmark>,
+) {
+    let name = node["name"].as_str().unwrap_or_default();
+    match node["type"].as_str() {
+        Some("url") => {
+            let Some(url) = node["url"].as_str() else {
+                return;
+            };
+            bookmarks.push(Bookmark {
+                title: if name.is_empty() { url } else { name }.to_string(),
+                url: url.to_string(),
+                folder: folder.to_string(),
+                browser: browser.to_string(),
+                favicon: None,
+            });
+        }
+        Some("folder") => {
+            let folder = match (folder.is_empty(), name.is_empty()) {
+                (_, true) => folder.to_string(),
+                (true, false) => name.to_string(),
+                (false, false) => format!("{}/{}", folder, name),
+            };
+            for child in node["children"].as_array().into_iter().flatten() {
+                collect_chromium(child, &folder, browser, bookmarks);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn set_favicons(profile: &Path, bookmarks: &mut [Bookmark]) {
+    let urls = bookmarks
+        .iter()
+        .map(|bookmark| bookmark.url.as_str())
+        .collect::<HashSet<_>>();
+    let favicons = cache_favicons(profile, &urls);
+    for bookmark in bookmarks.iter_mut() {
+        bookmark.favicon = favicons.get(&bookmark.url).cloned();
+    }
+}
+
+/// write the favicons of the pages from the profile to the cache
+///
+/// works for firefox and chromium profiles, gives the cached file by page url
+pub fn cache_favicons(profile: &Path, urls: &HashSet<&str>) -> HashMap<String, PathBuf> {
+    // the best icon of a page comes last
+    let (database, query) = if profile.join("favicons.sqlite").is_file() {
+        (
+            profile.join("favicons.sqlite"),
+            "SELECT p.page_url, i.data FROM moz_pages_w_icons p
+             JOIN moz_icons_to_pages ip ON ip.page_id = p.id
+             JOIN moz_icons i ON i.id = ip.icon_id
+             WHERE i.data IS NOT NULL
+             ORDER BY ABS(i.width - 32) DESC",
+        )
+    } else if profile.join("Favicons").is_file() {
+        (
+            profile.join("Favicons"),
+            "SELECT m.page_url, b.image_data FROM icon_mapping m
+             JOIN favicon_bitmaps b ON b.icon_id = m.icon_id
+             ORDER BY ABS(b.width - 32) DESC",
+        )
+    } else {
+        return HashMap::new();
+    };
+
+    match read_favicons(&database, query, urls) {
+        Ok(favicons) => favicons,
+        Err(e) => {
+            LOG.debug(&format!("no favicons from {}: {}", database.display(), e));
+            HashMap::new()
+        }
+    }
+}
+
+fn read_favicons(
+    database: &Path,
+    query: &str,
+    urls: &HashSet<&str>,
+) -> Result<HashMap<String, PathBuf>, Box<dyn std::error::Error>> {
+    let copy = DatabaseCopy::open(database)?;
+    let mut statement = copy.connection().prepare(query)?;
+    let mut icons = HashMap::new();
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+    })?;
+    for (url, data) in rows.flatten() {
+        if urls.contains(url.as_str()) {
+            icons.insert(url, data);
+        }
+    }
+
+    let dir = favicons_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    let mut favicons = HashMap::new();
+    for (url, data) in icons {
+        let path = dir.join(favicon_name(&url));
+        if path.is_file() || std::fs::write(&path, data).is_ok() {
+            favicons.insert(url, path);
+        }
+    }
+    Ok(favicons)
+}
+
+/// favicon of the url's host, if a page of it had one cached
+pub fn cached_favicon(url: &str) -> Option<PathBuf> {
+    let path = favicons_dir().join(favicon_name(url));
+    path.is_file().then_some(path)
+}
+
+/// file name of the cached favicon, one per host
+fn favicon_name(url: &str) -> String {
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    host.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "starlight-test-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn copies_into_a_private_file() {
+        let dir = TempDir::new("copy-private");
+        let (from, to) = (dir.0.join("places.sqlite"), dir.0.join("copy.sqlite"));
+        std::fs::write(&from, "data").unwrap();
+
+        copy_private(&from, &to).unwrap();
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "data");
+        let mode = std::fs::metadata(&to).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn never_writes_through_existing_files() {
+        let dir = TempDir::new("copy-existing");
+        let from = dir.0.join("places.sqlite");
+        let (existing, link) = (dir.0.join("existing"), dir.0.join("link"));
+        std::fs::write(&from, "data").unwrap();
+        std::fs::write(&existing, "keep").unwrap();
+        std::os::unix::fs::symlink(&existing, &link).unwrap();
+
+        assert!(copy_private(&from, &existing).is_err());
+        assert!(copy_private(&from, &link).is_err());
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "keep");
+    }
+
+    #[test]
+    fn names_the_wal_file() {
+        assert_eq!(
+            wal_path(Path::new("/tmp/places.sqlite")),
+            PathBuf::from("/tmp/places.sqlite-wal")
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::utils::{
+    applications::{ApplicationError, ApplicationManager},
+    config::CONFIG,
+    logger::{LogLevel, Logger},
+};
+use tokio::sync::OnceCell;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("browser", LogLevel::Debug);
+}
+
+/// private window flags, by a part of the program name or desktop file id
+const PRIVATE_FLAGS: &[(&str, &str)] = &[
+    ("firefox", "--private-window"),
+    ("librewolf", "--private-window"),
+    ("waterfox", "--private-window"),
+    ("floorp", "--private-window"),
+    ("zen-browser", "--private-window"),
+    ("zen_browser", "--private-window"),
+    ("chromium", "--incognito"),
+    ("chrome", "--incognito"),
+    ("brave", "--incognito"),
+    ("vivaldi", "--incognito"),
+    ("microsoft-edge", "--inprivate"),
+    ("msedge", "--inprivate"),
+    ("opera", "--private"),
+    ("epiphany", "--incognito-mode"),
+];
+
+/// desktop file id of the default browser, asked once
+static DEFAULT_BROWSER: OnceCell<Option<String>> = OnceCell::const_new();
+
+#[derive(Debug)]
+pub enum BrowserError {
+    /// no application with the desktop file id
+    NotFound(String),
+    /// private window flag of the browser is unknown
+    NoPrivateWindow(String),
+    Launch(ApplicationError),
+}
+
+impl std::fmt::Display for BrowserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrowserError::NotFound(id) => write!(f, "browser '{}' is not installed", id),
+            BrowserError::NoPrivateWindow(name) => write!(
+                f,
+                "don't know how to open a private window of {}, set private_args",
+                name
+            ),
+            BrowserError::Launch(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// browser urls are opened with
+#[derive(Debug, Clone, PartialEq)]
+pub enum Browser {
+    /// desktop application by its desktop file id
+    Desktop(String),
+    /// command line, `{}` is replaced by the url
+    Command(String),
+}
+
+impl Browser {
+    fn parse(value: &str) -> Self {
+        if value.contains(char::is_whitespace) || value.contains("{}") {
+            Browser::Command(value.to_string())
+        } else {
+            Browser::Desktop(value.trim_end_matches(".desktop").to_string())
+        }
+    }
+
+    /// configured browser, or the default browser of the desktop
+    pub async fn detect() -> Option<Self> {
+        let configured = CONFIG
+            .web
+            .browser
+            .as_deref()
+            .map(str::trim)
+            .filter(|browser| !browser.is_empty());
+        match configured {
+            Some(browser) => Some(Self::parse(browser)),
+            None => default_browser().await.map(Browser::Desktop),
+        }
+    }
+}
+
+async fn default_browser() -> Option<String> {
+    DEFAULT_BROWSER
+        .get_or_init(|| async {
+            let output = tokio::process::Command::new("xdg-settings")
+                .args(["get", "default-web-browser"])
+                .output()
+                .await
+                .ok()?;
+            let id = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .trim_end_matches(".desktop")
+                .to_string();
+            LOG.debug(&format!("default browser: {}", id));
+            (output.status.success() && !id.is_empty()).then_some(id)
+        })
+        .await
+        .clone()
+}
+
+/// arguments opening a private window of the browser known by these names
+fn private_args(names: &[&str]) -> Option<Vec<String>> {
+    if let Some(args) = &CONFIG.web.private_args {
+        return Some(args.split_whitespace().map(String::from).collect());
+    }
+
+    names.iter().find_map(|name| {
+        let name = name.to_lowercase();
+        PRIVATE_FLAGS
+            .iter()
+            .find(|(browser, _)| name.contains(browser))
+            .map(|(_, flag)| vec![flag.to_string()])
+    })
+}
+
+/// open the url in the browser, through `xdg-open` when none is known
+pub async fn open_url(
+    apps: &ApplicationManager,
+    url: &str,
+    private: bool,
+) -> Result<(), BrowserError> {
+    match Browser::detect().await {
+        Some(Browser::Desktop(id)) => {
+            let Some(app) = apps.get_application(&id) else {
+                if private {
+                    return Err(BrowserError::NotFound(id));
+                }
+                LOG.warn(&format!("Browser '{}' not found, using xdg-open", id));
+                return xdg_open(url);
+            };
+
+            let mut args = Vec::new();
+            if private {
+                let names = [app.program_name().unwrap_or_default(), id.as_str()];
+                args = private_args(&names)
+                    .ok_or_else(|| BrowserError::NoPrivateWindow(app.name.clone()))?;
+            }
+            args.push(url.to_string());
+
+            apps.launch_application_with(app, &args)
+                .await
+                .map_err(BrowserError::Launch)
+        }
+        Some(Browser::Command(template)) => {
+            let mut argv = template
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            let program = argv.remove(0);
+
+            let mut args = Vec::new();
+            if private {
+                let name = program.rsplit('/').next().unwrap_or(&program);
+                args = private_args(&[name])
+                    .ok_or_else(|| BrowserError::NoPrivateWindow(name.to_string()))?;
+            }
+            if argv.iter().any(|arg| arg.contains("{}")) {
+                args.extend(argv.iter().map(|arg| arg.replace("{}", url)));
+            } else {
+                args.extend(argv);
+                args.push(url.to_string());
+            }
+
+            let mut command = tokio::process::Command::new(&program);
+            command.args(&args);
+            ApplicationManager::spawn_detached(command, &program).map_err(BrowserError::Launch)
+        }
+        None if private => Err(BrowserError::NoPrivateWindow(
+            "the default browser".to_string(),
+        )),
+        None => xdg_open(url),
+    }
+}
+
+fn xdg_open(url: &str) -> Result<(), BrowserError> {
+    let mut command = tokio::process::Command::new("xdg-open");
+    command.arg(url);
+    ApplicationManager::spawn_detached(command, "xdg-open").map_err(BrowserError::Launch)
+}
@@ -0,0 +1,209 @@
+use crate::utils::{
+    bookmarks::{DatabaseCopy, cache_favicons, chromium_profiles, firefox_profiles, match_score},
+    logger::{LogLevel, Logger},
+};
+use chrono::{Local, TimeZone};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("browser_history", LogLevel::Debug);
+}
+
+// most recent pages read from one profile
+const MAX_PAGES_PER_PROFILE: u32 = 5000;
+// history rows shown for one query
+const MAX_RESULTS: usize = 10;
+// chromium counts microseconds from 1601-01-01, unix time from 1970-01-01
+const CHROMIUM_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+
+/// page from the browser history
+#[derive(Debug, Clone)]
+pub struct Visit {
+    pub title: String,
+    pub url: String,
+    /// browser the page was visited with
+    pub browser: String,
+    pub visit_count: u32,
+    /// unix time of the last visit
+    pub last_visit: i64,
+    /// cached favicon file
+    pub favicon: Option<PathBuf>,
+}
+
+impl Visit {
+    /// when the page was visited last, e.g. `yesterday 14:02` or `12 Mar 2025`
+    pub fn last_visit_label(&self) -> String {
+        let Some(visited) = Local.timestamp_opt(self.last_visit, 0).single() else {
+            return String::new();
+        };
+        let days = Local::now()
+            .date_naive()
+            .signed_duration_since(visited.date_naive())
+            .num_days();
+        match days {
+            0 => format!("today {}", visited.format("%H:%M")),
+            1 => format!("yesterday {}", visited.format("%H:%M")),
+            2..=6 => visited.format("%A %H:%M").to_string(),
+            _ => visited.format("%-d %b %Y").to_string(),
+        }
+    }
+
+    /// often and recently visited pages rank higher
+    fn frecency(&self, now: i64) -> f64 {
+        let age_days = (now - self.last_visit).max(0) as f64 / 86_400.0;
+        (1.0 + f64::from(self.visit_count).ln_1p()) / (1.0 + age_days / 7.0)
+    }
+}
+
+/// pages visited within the last `max_age_days` in all detected profiles
+///
+/// a page visited in several profiles is listed once with the visits added up
+pub fn load_history(max_age_days: u32) -> Vec<Visit> {
+    let since = Local::now().timestamp() - i64::from(max_age_days) * 86_400;
+
+    let mut visits = Vec::new();
+    for (browser, profile) in firefox_profiles() {
+        match read_firefox(&profile, browser, since) {
+            Ok(found) => visits.extend(found),
+            Err(e) => LOG.warn(&format!(
+                "Failed to read history of {}: {}",
+                profile.display(),
+                e
+            )),
+        }
+    }
+    for (browser, profile) in chromium_profiles() {
+        if !profile.join("History").is_file() {
+            continue;
+        }
+        match read_chromium(&profile, browser, since) {
+            Ok(found) => visits.extend(found),
+            Err(e) => LOG.warn(&format!(
+                "Failed to read history of {}: {}",
+                profile.display(),
+                e
+            )),
+        }
+    }
+
+    let mut merged: HashMap<String, Visit> = HashMap::new();
+    for visit in visits {
+        match merged.get_mut(&visit.url) {
+            Some(existing) => {
+                existing.visit_count += visit.visit_count;
+                if visit.last_visit > existing.last_visit {
+                    existing.last_visit = visit.last_visit;
+                    existing.title = visit.title;
+                }
+                existing.favicon = existing.favicon.take().or(visit.favicon);
+            }
+            None => {
+                merged.insert(visit.url.clone(), visit);
+            }
+        }
+    }
+
+    let mut visits = merged.into_values().collect::<Vec<_>>();
+    visits.sort_by_key(|visit| Reverse(visit.last_visit));
+    LOG.debug(&format!("loaded {} history pages", visits.len()));
+    visits
+}
+
+/// pages matching every word of the query, ranked by the match, visit count
+/// and last visit
+pub fn search_history<'a>(visits: &'a [Visit], query: &str) -> Vec<&'a Visit> {
+    let words = query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let now = Local::now().timestamp();
+    let mut matches = visits
+        .iter()
+        .filter_map(|visit| {
+            let score = match_score(&words, &visit.title, &visit.url, "")?;
+            Some((f64::from(score) * visit.frecency(now), visit))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, visit)| visit)
+        .collect()
+}
+
+fn read_firefox(
+    profile: &Path,
+    browser: &str,
+    since: i64,
+) -> Result<Vec<Visit>, Box<dyn std::error::Error>> {
+    let places = DatabaseCopy::open(&profile.join("places.sqlite"))?;
+    // last_visit_date is in microseconds
+    let mut statement = places.connection().prepare(
+        "SELECT url, title, visit_count, last_visit_date / 1000000 FROM moz_places
+         WHERE hidden = 0 AND visit_count > 0 AND last_visit_date >= ?1 * 1000000
+         ORDER BY last_visit_date DESC LIMIT ?2",
+    )?;
+    let visits = statement
+        .query_map((since, MAX_PAGES_PER_PROFILE), |row| {
+            visit_from_row(row, browser)
+        })?
+        .flatten()
+        .collect();
+    Ok(with_favicons(profile, visits))
+}
+
+fn read_chromium(
+    profile: &Path,
+    browser: &str,
+    since: i64,
+) -> Result<Vec<Visit>, Box<dyn std::error::Error>> {
+    let history = DatabaseCopy::open(&profile.join("History"))?;
+    let mut statement = history.connection().prepare(
+        "SELECT url, title, visit_count, last_visit_time / 1000000 - ?3 FROM urls
+         WHERE hidden = 0 AND visit_count > 0 AND last_visit_time >= (?1 + ?3) * 1000000
+         ORDER BY last_visit_time DESC LIMIT ?2",
+    )?;
+    let visits = statement
+        .query_map(
+            (since, MAX_PAGES_PER_PROFILE, CHROMIUM_EPOCH_OFFSET_SECS),
+            |row| visit_from_row(row, browser),
+        )?
+        .flatten()
+        .collect();
+    Ok(with_favicons(profile, visits))
+}
+
+/// visit from a row of url, title, visit count and unix time of the last visit
+fn visit_from_row(row: &rusqlite::Row, browser: &str) -> rusqlite::Result<Visit> {
+    let url = row.get::<_, String>(0)?;
+    Ok(Visit {
+        title: row
+            .get::<_, Option<String>>(1)?
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| url.clone()),
+        url,
+        browser: browser.to_string(),
+        visit_count: row.get(2)?,
+        last_visit: row.get(3)?,
+        favicon: None,
+    })
+}
+
+fn with_favicons(profile: &Path, mut visits: Vec<Visit>) -> Vec<Visit> {
+    let urls = visits
+        .iter()
+        .map(|visit| visit.url.as_str())
+        .collect::<HashSet<_>>();
+    let favicons = cache_favicons(profile, &urls);
+    for visit in &mut visits {
+        visit.favicon = favicons.get(&visit.url).cloned();
+    }
+    visits
+}
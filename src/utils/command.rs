@@ -1,42 +1,202 @@
-use crate::utils::logger::{LogLevel, Logger};
+use crate::utils::{
+    config::CONFIG,
+    logger::{LogLevel, Logger},
+    shell::{async_shell_command, path_var, shell_argv, shell_command},
+    terminal::{Terminal, needs_terminal, run_in_terminal},
+};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::RwLock;
 
 lazy_static::lazy_static! {
     static ref LOG: Logger = Logger::new("command", LogLevel::Debug);
 }
 
-pub async fn get_executables_from_path() -> Vec<String> {
-    let paths = std::env::var("PATH").unwrap_or_default();
-    let mut executables = Vec::new();
+/// executable found in one of the PATH directories
+#[derive(Debug, Clone)]
+pub struct Executable {
+    pub name: String,
+    pub path: PathBuf,
+    pub dir: PathBuf,
+}
+
+/// executables of a single directory, valid as long as its mtime doesn't change
+#[derive(Debug, Clone)]
+struct CachedDirectory {
+    mtime: Option<SystemTime>,
+    executables: Vec<Executable>,
+}
+
+/// index of all executables in PATH
+///
+/// directories are only rescanned when their mtime changes, which happens
+/// whenever a file is added, removed or renamed in them
+#[derive(Debug, Default)]
+pub struct ExecutableIndex {
+    directories: Vec<PathBuf>,
+    cache: HashMap<PathBuf, CachedDirectory>,
+    // unique by name, the first directory in PATH wins like in the shell
+    executables: Vec<Executable>,
+}
+
+impl ExecutableIndex {
+    pub fn new() -> Self {
+        let mut index = Self::default();
+        index.set_path_var(&path_var());
+        index
+    }
+
+    /// use the directories of another PATH, keeping what was already scanned
+    pub fn set_path_var(&mut self, path_var: &str) {
+        let mut seen = HashSet::new();
+        self.directories = path_var
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .filter(|dir| seen.insert(dir.clone()))
+            .collect();
+        self.rebuild();
+    }
 
-    for path in paths.split(':') {
-        if let Ok(entries) = std::fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let file = entry.path();
-                if file.is_file() && is_executable(&file) {
-                    if let Some(name) = file.file_name().and_then(|n| n.to_str()) {
-                        executables.push(name.to_string());
+    /// directories that were never scanned or changed since the last scan
+    pub fn stale_directories(&self) -> Vec<PathBuf> {
+        self.directories
+            .iter()
+            .filter(|dir| match self.cache.get(*dir) {
+                Some(cached) => cached.mtime != directory_mtime(dir),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// refresh a shared index without blocking the caller while scanning
+    pub async fn refresh_shared(index: &Arc<RwLock<ExecutableIndex>>) {
+        let stale = index.read().await.stale_directories();
+        if stale.is_empty() {
+            return;
+        }
+
+        let scanned = tokio::task::spawn_blocking(move || {
+            stale
+                .into_iter()
+                .map(|dir| {
+                    let cached = scan_directory(&dir);
+                    (dir, cached)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+        match scanned {
+            Ok(scanned) => index.write().await.apply(scanned),
+            Err(e) => LOG.error(&format!("Failed to scan PATH: {:?}", e)),
+        }
+    }
+
+    fn apply(&mut self, scanned: Vec<(PathBuf, CachedDirectory)>) {
+        for (dir, cached) in scanned {
+            LOG.debug(&format!(
+                "indexed {} executables in {}",
+                cached.executables.len(),
+                dir.display()
+            ));
+            self.cache.insert(dir, cached);
+        }
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        let mut seen = HashSet::new();
+        let mut executables = Vec::new();
+
+        for dir in &self.directories {
+            if let Some(cached) = self.cache.get(dir) {
+                for executable in &cached.executables {
+                    if seen.insert(executable.name.clone()) {
+                        executables.push(executable.clone());
                     }
                 }
             }
         }
+
+        executables.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        self.executables = executables;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Executable> {
+        self.executables
+            .binary_search_by(|e| e.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.executables[index])
+    }
+
+    pub fn search(&self, query: &str) -> Vec<&Executable> {
+        self.executables
+            .iter()
+            .filter(|e| e.name.contains(query))
+            .collect()
+    }
+}
+
+fn directory_mtime(dir: &Path) -> Option<SystemTime> {
+    dir.metadata().and_then(|m| m.modified()).ok()
+}
+
+fn scan_directory(dir: &Path) -> CachedDirectory {
+    let mtime = directory_mtime(dir);
+    let mut executables = Vec::new();
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let file = entry.path();
+            if file.is_file()
+                && is_executable(&file)
+                && let Some(name) = file.file_name().and_then(|n| n.to_str())
+            {
+                executables.push(Executable {
+                    name: name.to_string(),
+                    path: file.clone(),
+                    dir: dir.to_path_buf(),
+                });
+            }
+        }
     }
 
-    executables.sort_unstable();
-    executables.dedup();
-    executables
+    CachedDirectory { mtime, executables }
 }
 
-fn is_executable(path: &std::path::Path) -> bool {
+pub fn is_executable(path: &std::path::Path) -> bool {
     use std::os::unix::fs::PermissionsExt;
     path.metadata()
         .map(|m| m.permissions().mode() & 0o111 != 0)
         .unwrap_or(false)
 }
 
+/// argv `run_command` executes for the command
+pub fn run_argv(command: &str) -> Vec<String> {
+    if needs_terminal(command)
+        && let Some(terminal) = Terminal::detect()
+    {
+        return terminal.wrap(command, CONFIG.run.hold_terminal);
+    }
+    shell_argv(command)
+}
+
 pub fn run_command(command: &str) {
-    let mut cmd = std::process::Command::new("sh");
-    cmd.arg("-c").arg(command);
+    if needs_terminal(command) {
+        if let Err(e) = run_in_terminal(command, CONFIG.run.hold_terminal) {
+            LOG.error(&format!("failed to run '{}' in terminal: {:?}", command, e));
+        }
+        return;
+    }
 
+    let mut cmd = shell_command(command);
     cmd.stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .stdin(std::process::Stdio::null());
@@ -48,3 +208,135 @@ pub fn run_command(command: &str) {
     }
 }
 
+/// how a captured command ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureStatus {
+    Exited(i32),
+    Signaled,
+    TimedOut,
+    Cancelled,
+    /// output went over the size limit, the command was stopped
+    Truncated,
+    Failed(String),
+}
+
+impl std::fmt::Display for CaptureStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureStatus::Exited(code) => write!(f, "exited with status {}", code),
+            CaptureStatus::Signaled => write!(f, "was killed by a signal"),
+            CaptureStatus::TimedOut => write!(f, "timed out"),
+            CaptureStatus::Cancelled => write!(f, "was cancelled"),
+            CaptureStatus::Truncated => write!(f, "was stopped, output too large"),
+            CaptureStatus::Failed(e) => write!(f, "failed to start: {}", e),
+        }
+    }
+}
+
+/// run a command and stream its stdout and stderr lines as they come
+///
+/// the command is killed when it runs longer than the timeout, prints more
+/// than `max_bytes` or when `cancel` resolves
+pub async fn capture_command(
+    command: &str,
+    timeout: Duration,
+    max_bytes: usize,
+    cancel: impl Future<Output = ()>,
+    mut on_line: impl FnMut(&str),
+) -> CaptureStatus {
+    let mut cmd = async_shell_command(command);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        // own process group, so pipelines and background jobs can be killed with it
+        .process_group(0);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            LOG.error(&format!("failed to run '{}': {:?}", command, e));
+            return CaptureStatus::Failed(e.to_string());
+        }
+    };
+    LOG.debug(&format!("capturing output of '{}'", command));
+
+    let mut stdout = child.stdout.take().map(|out| BufReader::new(out).lines());
+    let mut stderr = child.stderr.take().map(|err| BufReader::new(err).lines());
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    tokio::pin!(cancel);
+    let mut captured = 0;
+
+    while stdout.is_some() || stderr.is_some() {
+        let line = tokio::select! {
+            line = next_line(&mut stdout), if stdout.is_some() => {
+                if line.is_none() {
+                    stdout = None;
+                }
+                line
+            }
+            line = next_line(&mut stderr), if stderr.is_some() => {
+                if line.is_none() {
+                    stderr = None;
+                }
+                line
+            }
+            _ = &mut deadline => {
+                kill_group(&mut child).await;
+                return CaptureStatus::TimedOut;
+            }
+            _ = &mut cancel => {
+                kill_group(&mut child).await;
+                return CaptureStatus::Cancelled;
+            }
+        };
+
+        if let Some(line) = line {
+            captured += line.len() + 1;
+            if captured > max_bytes {
+                kill_group(&mut child).await;
+                return CaptureStatus::Truncated;
+            }
+            on_line(&line);
+        }
+    }
+
+    let status = tokio::select! {
+        status = child.wait() => status,
+        _ = &mut deadline => {
+            kill_group(&mut child).await;
+            return CaptureStatus::TimedOut;
+        }
+        _ = &mut cancel => {
+            kill_group(&mut child).await;
+            return CaptureStatus::Cancelled;
+        }
+    };
+
+    match status {
+        Ok(status) => match status.code() {
+            Some(code) => CaptureStatus::Exited(code),
+            None => CaptureStatus::Signaled,
+        },
+        Err(e) => CaptureStatus::Failed(e.to_string()),
+    }
+}
+
+/// kill the whole process group of the child, not only the shell running it
+async fn kill_group(child: &mut tokio::process::Child) {
+    if let Some(pid) = child.id() {
+        // SAFETY: kill has no memory safety requirements
+        unsafe { libc::kill(-(pid as i32), libc::SIGKILL) };
+    }
+    let _ = child.kill().await;
+}
+
+async fn next_line<R: tokio::io::AsyncBufRead + Unpin>(
+    lines: &mut Option<tokio::io::Lines<R>>,
+) -> Option<String> {
+    match lines {
+        Some(lines) => lines.next_line().await.ok().flatten(),
+        None => None,
+    }
+}
@@ -0,0 +1,416 @@
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    shell::login_env,
+};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("completion", LogLevel::Debug);
+}
+
+const MAX_COMPLETIONS: usize = 200;
+const SHELL_TIMEOUT: Duration = Duration::from_millis(1500);
+
+const BASH_COMPLETION_SCRIPTS: &[&str] = &[
+    "/usr/share/bash-completion/bash_completion",
+    "/etc/bash_completion",
+    "/usr/local/share/bash-completion/bash_completion",
+];
+
+// loads the completion spec of the command through bash-completion
+// and prints the candidates it produces for the last word, one per line
+const BASH_COMPLETE_SCRIPT: &str = r#"
+. "$1" >/dev/null 2>&1
+COMP_LINE=$2
+COMP_POINT=${#COMP_LINE}
+read -ra COMP_WORDS <<< "$COMP_LINE"
+[[ $COMP_LINE == *' ' ]] && COMP_WORDS+=('')
+COMP_CWORD=$(( ${#COMP_WORDS[@]} - 1 ))
+cmd=${COMP_WORDS[0]}
+__load_completion "$cmd" >/dev/null 2>&1 || _completion_loader "$cmd" >/dev/null 2>&1
+spec=$(complete -p "$cmd" 2>/dev/null) || exit 0
+[[ $spec =~ -F\ ([^ ]+) ]] || exit 0
+"${BASH_REMATCH[1]}" "$cmd" "${COMP_WORDS[COMP_CWORD]}" "${COMP_WORDS[COMP_CWORD-1]}" >/dev/null 2>&1
+printf '%s\n' "${COMPREPLY[@]}"
+"#;
+
+/// candidate for the word under the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// full command line with the last word completed
+    pub line: String,
+    /// the completed word itself
+    pub word: String,
+    pub description: Option<String>,
+}
+
+/// which shell completion system to ask for arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionShell {
+    Fish,
+    Bash(PathBuf),
+    None,
+}
+
+impl CompletionShell {
+    /// fish when it is the user's shell, otherwise bash-completion if installed
+    pub fn detect() -> Self {
+        let shell = std::env::var("SHELL").unwrap_or_default();
+        if shell.ends_with("/fish") && which::which("fish").is_ok() {
+            return CompletionShell::Fish;
+        }
+
+        BASH_COMPLETION_SCRIPTS
+            .iter()
+            .map(Path::new)
+            .find(|script| script.exists())
+            .filter(|_| which::which("bash").is_ok())
+            .map(|script| CompletionShell::Bash(script.to_path_buf()))
+            .unwrap_or(CompletionShell::None)
+    }
+}
+
+/// whether the word should be completed against the file system
+pub fn is_path_like(word: &str) -> bool {
+    word.starts_with('/')
+        || word.starts_with('~')
+        || word.starts_with("./")
+        || word.starts_with("../")
+        || word.starts_with('$')
+        || word.contains('/')
+}
+
+/// split a command line into everything before the last word and the last word
+pub fn split_last_word(line: &str) -> (&str, &str) {
+    match line.rfind(char::is_whitespace) {
+        Some(index) => line.split_at(index + 1),
+        None => ("", line),
+    }
+}
+
+/// complete the last word of a run mode command line
+///
+/// paths are completed from the file system, arguments are asked from the
+/// shell completion system and fall back to files of the working directory
+pub async fn complete(line: &str, shell: &CompletionShell) -> Vec<Completion> {
+    let (head, word) = split_last_word(line);
+
+    // the first word is a command, which the executable index handles
+    if head.trim().is_empty() && !is_path_like(word) {
+        return Vec::new();
+    }
+
+    if is_path_like(word) {
+        return complete_path(head, word);
+    }
+
+    let mut completions = complete_arguments(line, shell).await;
+    if completions.is_empty() {
+        completions = complete_path(head, word);
+    }
+    completions
+}
+
+/// expand a leading `~` and `$VAR` or `${VAR}` references
+pub fn expand_path(word: &str) -> PathBuf {
+    let word = match word.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", dirs::home_dir().unwrap_or_default().display(), rest)
+        }
+        _ => word.to_string(),
+    };
+
+    let mut expanded = String::new();
+    let mut chars = word.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next.is_ascii_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if braced && chars.peek() == Some(&'}') {
+            chars.next();
+        }
+
+        if name.is_empty() {
+            expanded.push('$');
+        } else {
+            expanded.push_str(&std::env::var(&name).unwrap_or_default());
+        }
+    }
+
+    PathBuf::from(expanded)
+}
+
+/// files and directories starting with the word, keeping the word as typed
+fn complete_path(head: &str, word: &str) -> Vec<Completion> {
+    let (typed_dir, partial) = match word.rfind('/') {
+        Some(index) => word.split_at(index + 1),
+        None => ("", word),
+    };
+    // a bare `~user` or `$VAR` has nothing to list yet
+    if typed_dir.is_empty() && (partial.starts_with('~') || partial.starts_with('$')) {
+        return Vec::new();
+    }
+    let dir = if typed_dir.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_path(typed_dir)
+    };
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut completions = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // hidden files only when asked for
+            if !name.starts_with(partial) || (name.starts_with('.') && !partial.starts_with('.')) {
+                return None;
+            }
+
+            let is_dir = entry.path().is_dir();
+            let word = format!(
+                "{}{}{}",
+                typed_dir,
+                escape_word(&name),
+                if is_dir { "/" } else { "" }
+            );
+            Some(Completion {
+                line: format!("{}{}", head, word),
+                word,
+                description: Some(if is_dir { "Directory" } else { "File" }.to_string()),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    completions.sort_by(|a, b| a.word.cmp(&b.word));
+    completions.truncate(MAX_COMPLETIONS);
+    completions
+}
+
+/// escape the characters the shell would interpret
+pub fn escape_word(word: &str) -> String {
+    let mut escaped = String::with_capacity(word.len());
+    for c in word.chars() {
+        if c.is_whitespace() || "'\"\\$`!&;|<>()*?#".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+async fn complete_arguments(line: &str, shell: &CompletionShell) -> Vec<Completion> {
+    let mut command = match shell {
+        CompletionShell::Fish => {
+            let mut command = Command::new("fish");
+            command
+                .arg("-c")
+                .arg("complete --do-complete=$argv[1]")
+                .arg(line);
+            command
+        }
+        CompletionShell::Bash(script) => {
+            let mut command = Command::new("bash");
+            command
+                .arg("-c")
+                .arg(BASH_COMPLETE_SCRIPT)
+                .arg("bash")
+                .arg(script)
+                .arg(line);
+            command
+        }
+        CompletionShell::None => return Vec::new(),
+    };
+    if let Some(env) = login_env() {
+        command.envs(env);
+    }
+    command
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    let output = match tokio::time::timeout(SHELL_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            LOG.error(&format!("Failed to run shell completion: {}", e));
+            return Vec::new();
+        }
+        Err(_) => {
+            LOG.warn(&format!("shell completion timed out for '{}'", line));
+            return Vec::new();
+        }
+    };
+
+    let (head, _) = split_last_word(line);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut completions = Vec::new();
+
+    for candidate in stdout.lines() {
+        // fish separates the description with a tab
+        let (word, description) = match candidate.split_once('\t') {
+            Some((word, description)) => (word.trim(), Some(description.trim().to_string())),
+            None => (candidate.trim(), None),
+        };
+        if word.is_empty() || completions.iter().any(|c: &Completion| c.word == word) {
+            continue;
+        }
+
+        completions.push(Completion {
+            line: format!("{}{}", head, word),
+            word: word.to_string(),
+            description,
+        });
+        if completions.len() >= MAX_COMPLETIONS {
+            break;
+        }
+    }
+
+    completions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// directory of its own per test with the given files, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str, files: &[&str]) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "starlight-test-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            for file in files {
+                let path = dir.join(file);
+                if file.ends_with('/') {
+                    std::fs::create_dir_all(&path).unwrap();
+                } else {
+                    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    std::fs::write(&path, "").unwrap();
+                }
+            }
+            Self(dir)
+        }
+
+        fn path(&self) -> String {
+            format!("{}/", self.0.display())
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn detects_path_like_words() {
+        for word in [
+            "/usr",
+            "~",
+            "~/src",
+            "./build",
+            "../up",
+            "$HOME",
+            "src/main.rs",
+        ] {
+            assert!(is_path_like(word), "{}", word);
+        }
+        for word in ["ls", "--help", "-la", "main.rs"] {
+            assert!(!is_path_like(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn splits_off_the_last_word() {
+        assert_eq!(split_last_word("git commit -m"), ("git commit ", "-m"));
+        assert_eq!(split_last_word("ls "), ("ls ", ""));
+        assert_eq!(split_last_word("ls"), ("", "ls"));
+    }
+
+    #[test]
+    fn expands_home_and_variables() {
+        let home = dirs::home_dir().unwrap_or_default();
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path("~/src"), home.join("src"));
+        // only the own home, `~user` stays as typed
+        assert_eq!(expand_path("~user/src"), PathBuf::from("~user/src"));
+
+        let path = std::env::var("PATH").unwrap_or_default();
+        assert_eq!(expand_path("$PATH/x"), PathBuf::from(format!("{}/x", path)));
+        assert_eq!(expand_path("${PATH}x"), PathBuf::from(format!("{}x", path)));
+        assert_eq!(expand_path("a$/b"), PathBuf::from("a$/b"));
+    }
+
+    #[test]
+    fn escapes_shell_characters() {
+        assert_eq!(escape_word("my file (1).txt"), "my\\ file\\ \\(1\\).txt");
+        assert_eq!(escape_word("plain"), "plain");
+    }
+
+    #[tokio::test]
+    async fn completes_paths_keeping_the_typed_directory() {
+        let dir = TempDir::new("paths", &["notes.txt", "notebooks/", "other", ".hidden"]);
+        let line = format!("cat {}no", dir.path());
+
+        let completions = complete(&line, &CompletionShell::None).await;
+
+        let words = completions
+            .iter()
+            .map(|c| c.word.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            words,
+            vec![
+                format!("{}notebooks/", dir.path()),
+                format!("{}notes.txt", dir.path()),
+            ]
+        );
+        assert_eq!(completions[0].line, format!("cat {}", words[0]));
+        assert_eq!(completions[0].description.as_deref(), Some("Directory"));
+        assert_eq!(completions[1].description.as_deref(), Some("File"));
+    }
+
+    #[tokio::test]
+    async fn lists_hidden_files_only_when_asked() {
+        let dir = TempDir::new("hidden", &["visible", ".hidden"]);
+
+        let all = complete(&format!("ls {}", dir.path()), &CompletionShell::None).await;
+        assert_eq!(all.len(), 1);
+
+        let hidden = complete(&format!("ls {}.", dir.path()), &CompletionShell::None).await;
+        assert_eq!(hidden[0].word, format!("{}.hidden", dir.path()));
+    }
+
+    #[tokio::test]
+    async fn leaves_the_command_word_to_the_executable_index() {
+        assert!(complete("fire", &CompletionShell::None).await.is_empty());
+
+        // a path as the command is completed like any other
+        let dir = TempDir::new("command", &["run.sh"]);
+        let completions = complete(&format!("{}r", dir.path()), &CompletionShell::None).await;
+        assert_eq!(completions[0].line, format!("{}run.sh", dir.path()));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::utils::logger::{LogLevel, Logger};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("config", LogLevel::Debug);
+}
+
+/// user configuration, read once from `~/.config/starlight/config.toml`
+pub static CONFIG: Lazy<Config> = Lazy::new(Config::load);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub search: SearchConfig,
+    pub run: RunConfig,
+    pub web: WebConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// delay after the last keystroke before a search starts
+    pub debounce_ms: u64,
+    /// show suggestions of the search engines in web mode
+    pub suggestions: bool,
+    /// milliseconds to wait for the suggestions of an engine
+    pub suggestion_timeout_ms: u64,
+    /// suggestions shown per engine
+    pub max_suggestions: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 80,
+            suggestions: true,
+            suggestion_timeout_ms: 1500,
+            max_suggestions: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunConfig {
+    /// number of commands kept in the history, 0 disables it
+    pub history_size: usize,
+    /// seconds a captured command may run before it gets killed
+    pub capture_timeout_secs: u64,
+    /// bytes of output shown for a captured command
+    pub capture_max_bytes: usize,
+    /// terminal emulator command, detected when not set
+    pub terminal: Option<String>,
+    /// keep the terminal open after the command exits
+    pub hold_terminal: bool,
+    /// programs that always run inside a terminal
+    pub terminal_programs: Vec<String>,
+    /// shell running the commands, `$SHELL` when not set
+    pub shell: Option<String>,
+    /// run commands through a login shell
+    pub login_shell: bool,
+    /// run commands through an interactive shell, so aliases and functions apply
+    pub interactive_shell: bool,
+    /// use the environment of the login shell, e.g. PATH set in `.zprofile`
+    pub import_login_env: bool,
+    /// how to run commands as root: `pkexec`, `sudo` or `auto` when not set
+    pub elevate: Option<String>,
+    /// askpass program for `sudo -A`, `$SUDO_ASKPASS` when not set
+    pub askpass: Option<String>,
+    /// ask before running commands that look destructive
+    pub confirm_dangerous: bool,
+    /// extra regexes of commands to confirm, next to the built-in ones
+    pub dangerous_patterns: Vec<String>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            history_size: 500,
+            capture_timeout_secs: 30,
+            capture_max_bytes: 256 * 1024,
+            terminal: None,
+            hold_terminal: false,
+            terminal_programs: [
+                "htop", "btop", "top", "nvim", "vim", "vi", "nano", "ssh", "less", "man",
+            ]
+            .iter()
+            .map(|program| program.to_string())
+            .collect(),
+            shell: None,
+            login_shell: false,
+            interactive_shell: false,
+            import_login_env: true,
+            elevate: None,
+            askpass: None,
+            confirm_dangerous: true,
+            dangerous_patterns: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebConfig {
+    /// desktop file id or command with `{}` for the url, the default browser when not set
+    pub browser: Option<String>,
+    /// arguments opening a private window, detected for known browsers when not set
+    pub private_args: Option<String>,
+    /// search the bookmarks of firefox and chromium based browsers
+    pub bookmarks: bool,
+    /// search the history of firefox and chromium based browsers
+    pub history: bool,
+    /// days of browser history to search
+    pub history_days: u32,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            browser: None,
+            private_args: None,
+            bookmarks: true,
+            history: true,
+            history_days: 30,
+        }
+    }
+}
+
+impl Config {
+    /// directory holding all user configuration files
+    pub fn dir() -> PathBuf {
+        dirs::config_dir().unwrap_or_default().join("starlight")
+    }
+
+    pub fn path() -> PathBuf {
+        Self::dir().join("config.toml")
+    }
+
+    /// load config from disk, falling back to defaults when missing or invalid
+    pub fn load() -> Self {
+        let path = Self::path();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => {
+                LOG.debug(&format!("no config at {}, using defaults", path.display()));
+                return Self::default();
+            }
+        };
+
+        match toml::from_str(&content) {
+            Ok(config) => {
+                LOG.debug(&format!("loaded config from {}", path.display()));
+                config
+            }
+            Err(e) => {
+                LOG.error(&format!("Failed to parse {}: {}", path.display(), e));
+                Self::default()
+            }
+        }
+    }
+}
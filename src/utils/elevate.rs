@@ -0,0 +1,106 @@
+use crate::utils::{
+    config::CONFIG,
+    logger::{LogLevel, Logger},
+    shell::shell_program,
+};
+use std::fmt;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("elevate", LogLevel::Debug);
+}
+
+#[derive(Debug)]
+pub enum ElevateError {
+    /// neither pkexec nor sudo with an askpass program is available
+    Unavailable,
+    Spawn(std::io::Error),
+}
+
+impl fmt::Display for ElevateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElevateError::Unavailable => {
+                write!(
+                    f,
+                    "neither pkexec nor sudo with an askpass program is available"
+                )
+            }
+            ElevateError::Spawn(e) => write!(f, "failed to start: {}", e),
+        }
+    }
+}
+
+/// how commands get root rights, both ask for the password in a graphical prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum Elevator {
+    /// polkit authentication agent
+    Pkexec,
+    /// `sudo -A` with the askpass program
+    Sudo { askpass: String },
+}
+
+impl Elevator {
+    /// configured method, otherwise pkexec and then sudo with an askpass program
+    pub fn detect() -> Option<Self> {
+        let method = CONFIG.run.elevate.as_deref().unwrap_or("auto");
+
+        let pkexec = || which::which("pkexec").is_ok().then_some(Elevator::Pkexec);
+        let sudo = || {
+            let askpass = CONFIG
+                .run
+                .askpass
+                .clone()
+                .or_else(|| std::env::var("SUDO_ASKPASS").ok())
+                .filter(|askpass| !askpass.trim().is_empty())?;
+            which::which("sudo")
+                .is_ok()
+                .then_some(Elevator::Sudo { askpass })
+        };
+
+        match method {
+            "pkexec" => pkexec(),
+            "sudo" => sudo(),
+            _ => pkexec().or_else(sudo),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Elevator::Pkexec => "pkexec",
+            Elevator::Sudo { .. } => "sudo",
+        }
+    }
+
+    /// argv running the shell command as root
+    pub fn argv(&self, command: &str) -> Vec<String> {
+        let mut argv = match self {
+            Elevator::Pkexec => vec!["pkexec".to_string()],
+            Elevator::Sudo { .. } => vec!["sudo".to_string(), "-A".to_string()],
+        };
+        argv.extend([shell_program(), "-c".to_string(), command.to_string()]);
+        argv
+    }
+}
+
+/// run a shell command as root, refusing when there is no way to ask for the password
+pub fn run_elevated(command: &str) -> Result<(), ElevateError> {
+    let elevator = Elevator::detect().ok_or(ElevateError::Unavailable)?;
+    let argv = elevator.argv(command);
+    LOG.debug(&format!("running elevated: {:?}", argv));
+
+    let mut cmd = std::process::Command::new(&argv[0]);
+    cmd.args(&argv[1..])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    if let Elevator::Sudo { askpass } = &elevator {
+        cmd.env("SUDO_ASKPASS", askpass);
+    }
+
+    let mut child = cmd.spawn().map_err(ElevateError::Spawn)?;
+    // reap the command once it finishes
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
@@ -0,0 +1,538 @@
+use crate::utils::{
+    config::Config,
+    logger::{LogLevel, Logger},
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml_edit::{ArrayOfTables, DocumentMut, Item};
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("engines", LogLevel::Debug);
+}
+
+/// search engines, read once from `~/.config/starlight/engines.toml`
+pub static ENGINES: Lazy<EnginesConfig> = Lazy::new(EnginesConfig::load);
+
+/// web search engine, `{}` or `{searchTerms}` in the url is replaced by the query
+#[derive(Debug, Clone)]
+pub struct SearchEngine {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// OpenSearch suggestions endpoint, `{}` is replaced by the query
+    pub suggest_url: Option<String>,
+    pub description: String,
+    /// icon file path or theme icon name
+    pub icon: Option<String>,
+    pub enabled: bool,
+    /// position in the list, lower comes first
+    pub order: i32,
+    /// keywords selecting only this engine, like `yt` for `!yt lofi`
+    pub keywords: Vec<String>,
+}
+
+impl SearchEngine {
+    fn new(id: &str, name: &str, url: &str, description: &str, order: i32) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: url.to_string(),
+            suggest_url: None,
+            description: description.to_string(),
+            icon: None,
+            enabled: true,
+            order,
+            keywords: Vec::new(),
+        }
+    }
+
+    fn with_suggest_url(mut self, suggest_url: &str) -> Self {
+        self.suggest_url = Some(suggest_url.to_string());
+        self
+    }
+
+    fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|keyword| keyword.to_string()).collect();
+        self
+    }
+
+    /// apply the fields set in the config file
+    fn apply(&mut self, entry: &EngineEntry) {
+        if let Some(name) = &entry.name {
+            self.name = name.clone();
+        }
+        if let Some(url) = &entry.url {
+            self.url = url.clone();
+        }
+        if let Some(suggest_url) = &entry.suggest_url {
+            // an empty url turns the suggestions of a built-in engine off
+            self.suggest_url = Some(suggest_url.clone()).filter(|url| !url.is_empty());
+        }
+        if let Some(description) = &entry.description {
+            self.description = description.clone();
+        }
+        if entry.icon.is_some() {
+            self.icon = entry.icon.clone();
+        }
+        if let Some(enabled) = entry.enabled {
+            self.enabled = enabled;
+        }
+        if let Some(order) = entry.order {
+            self.order = order;
+        }
+        if let Some(keywords) = &entry.keywords {
+            self.keywords = keywords.clone();
+        }
+    }
+
+    /// url searching the query on this engine
+    pub fn search_url(&self, query: &str) -> String {
+        fill_template(&self.url, query)
+    }
+
+    /// url fetching the suggestions for the query, if the engine has any
+    pub fn suggestions_url(&self, query: &str) -> Option<String> {
+        self.suggest_url
+            .as_ref()
+            .map(|url| fill_template(url, query))
+    }
+}
+
+/// url of the template for the query
+///
+/// `{}` and the OpenSearch `{searchTerms}` become the query, the encodings
+/// and language get their usual values and other parameters are left empty
+pub fn fill_template(template: &str, query: &str) -> String {
+    let mut url = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        url.push_str(&rest[..start]);
+        // optional parameters end with `?`, e.g. `{startPage?}`
+        match rest[start + 1..end].trim_end_matches('?') {
+            "" | "searchTerms" => url.push_str(&urlencoding::encode(query)),
+            "inputEncoding" | "outputEncoding" => url.push_str("UTF-8"),
+            "language" => url.push('*'),
+            _ => {}
+        }
+        rest = &rest[end + 1..];
+    }
+    url.push_str(rest);
+    url
+}
+
+/// engine as written in the config file
+///
+/// an entry with the id of a built-in engine only changes the fields it sets,
+/// e.g. `enabled = false`, other entries add a new engine and need a url
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineEntry {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggest_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+/// bang in the config file, either just the url or with a name and icon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BangEntry {
+    Url(String),
+    Engine {
+        name: String,
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+    },
+}
+
+/// contents of `engines.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnginesFile {
+    /// id of the engine listed first, so Enter searches with it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(rename = "engine")]
+    pub engines: Vec<EngineEntry>,
+    /// extra `!keyword` shortcuts by their keyword
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub bangs: BTreeMap<String, BangEntry>,
+}
+
+impl EnginesFile {
+    /// contents of the file, empty when it doesn't exist yet
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// write the engine to the file, replacing the entry with the same id
+    pub fn save_engine(path: &Path, entry: &EngineEntry) -> Result<(), Box<dyn std::error::Error>> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, with_engine(&content, entry)?)?;
+        Ok(())
+    }
+}
+
+/// file content with the engine's `[[engine]]` table added or replaced
+///
+/// edited in place, so the comments and layout of the rest of the file stay
+fn with_engine(content: &str, entry: &EngineEntry) -> Result<String, Box<dyn std::error::Error>> {
+    let mut document = content.parse::<DocumentMut>()?;
+    let table = toml::to_string(entry)?
+        .parse::<DocumentMut>()?
+        .as_table()
+        .clone();
+
+    let engines = document
+        .entry("engine")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or("`engine` is not a list of tables")?;
+    let existing = engines
+        .iter_mut()
+        .find(|engine| engine.get("id").and_then(Item::as_str) == Some(entry.id.as_str()));
+    match existing {
+        Some(existing) => {
+            // keep the comments above the replaced table
+            let decor = existing.decor().clone();
+            *existing = table;
+            *existing.decor_mut() = decor;
+        }
+        None => engines.push(table),
+    }
+    Ok(document.to_string())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EnginesConfig {
+    pub default: Option<String>,
+    pub engines: Vec<SearchEngine>,
+    /// engines only reachable through their keyword, never listed
+    pub bangs: Vec<SearchEngine>,
+}
+
+impl EnginesConfig {
+    pub fn path() -> PathBuf {
+        Config::dir().join("engines.toml")
+    }
+
+    /// built-in engines changed and extended by the config file
+    pub fn load() -> Self {
+        let path = Self::path();
+        let file = match EnginesFile::read(&path) {
+            Ok(file) => {
+                LOG.debug(&format!(
+                    "loaded {} engines from {}",
+                    file.engines.len(),
+                    path.display()
+                ));
+                file
+            }
+            Err(e) => {
+                LOG.error(&format!("Failed to read {}: {}", path.display(), e));
+                EnginesFile::default()
+            }
+        };
+
+        let mut engines = built_in_engines();
+        for entry in &file.engines {
+            if let Some(engine) = engines.iter_mut().find(|engine| engine.id == entry.id) {
+                engine.apply(entry);
+                continue;
+            }
+
+            let Some(url) = entry.url.as_ref().filter(|_| !entry.id.is_empty()) else {
+                LOG.warn(&format!("skipping engine '{}' without id or url", entry.id));
+                continue;
+            };
+            let mut engine = SearchEngine::new(
+                &entry.id,
+                entry.name.as_deref().unwrap_or(&entry.id),
+                url,
+                "Web search",
+                engines.len() as i32,
+            );
+            engine.apply(entry);
+            engines.push(engine);
+        }
+
+        // user bangs first, so they win over the built-in ones
+        let user_bangs = file.bangs.iter().map(|(keyword, bang)| {
+            let engine = match bang {
+                BangEntry::Url(url) => SearchEngine::new(keyword, keyword, url, "Web search", 0),
+                BangEntry::Engine { name, url, icon } => {
+                    let mut engine = SearchEngine::new(keyword, name, url, "Web search", 0);
+                    engine.icon = icon.clone();
+                    engine
+                }
+            };
+            engine.with_keywords(&[keyword])
+        });
+        let bangs = user_bangs.chain(built_in_bangs()).collect();
+
+        Self {
+            default: file.default,
+            engines,
+            bangs,
+        }
+    }
+
+    /// engine selected by the keyword, engines of the list before bangs
+    pub fn by_keyword(&self, keyword: &str) -> Option<&SearchEngine> {
+        let keyword = keyword.to_lowercase();
+        self.engines
+            .iter()
+            .filter(|engine| engine.enabled)
+            .chain(&self.bangs)
+            .find(|engine| engine.keywords.contains(&keyword))
+    }
+
+    /// engine selected by a keyword typed without `!`
+    ///
+    /// single letters still need the `!`, `a` or `w` start plenty of queries
+    pub fn by_engine_keyword(&self, keyword: &str) -> Option<&SearchEngine> {
+        if keyword.chars().count() < 2 {
+            return None;
+        }
+        self.by_keyword(keyword)
+    }
+
+    /// enabled engines in order, the default one first
+    pub fn enabled(&self) -> Vec<&SearchEngine> {
+        let mut engines = self
+            .engines
+            .iter()
+            .filter(|engine| engine.enabled)
+            .collect::<Vec<_>>();
+        // stable, so engines with the same order keep their position
+        engines.sort_by_key(|engine| (Some(&engine.id) != self.default.as_ref(), engine.order));
+        engines
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SearchEngine> {
+        self.engines.iter().find(|engine| engine.id == id)
+    }
+}
+
+fn built_in_engines() -> Vec<SearchEngine> {
+    vec![
+        SearchEngine::new(
+            "duckduckgo",
+            "DuckDuckGo",
+            "https://duckduckgo.com/?q={}",
+            "Search with DuckDuckGo (Privacy-focused)",
+            0,
+        )
+        .with_suggest_url("https://duckduckgo.com/ac/?q={}&type=list")
+        .with_keywords(&["ddg", "d"]),
+        SearchEngine::new(
+            "google",
+            "Google",
+            "https://www.google.com/search?q={}",
+            "Search with Google",
+            1,
+        )
+        .with_suggest_url("https://suggestqueries.google.com/complete/search?client=firefox&q={}")
+        .with_keywords(&["g"]),
+        SearchEngine::new(
+            "youtube",
+            "YouTube",
+            "https://www.youtube.com/results?search_query={}",
+            "Search YouTube videos",
+            2,
+        )
+        .with_suggest_url(
+            "https://suggestqueries.google.com/complete/search?client=firefox&ds=yt&q={}",
+        )
+        .with_keywords(&["yt"]),
+        SearchEngine::new(
+            "stackoverflow",
+            "Stack Overflow",
+            "https://stackoverflow.com/search?q={}",
+            "Search Stack Overflow",
+            3,
+        )
+        .with_keywords(&["so"]),
+    ]
+}
+
+/// popular DuckDuckGo bangs, keyword, name and url
+const BUILT_IN_BANGS: &[(&str, &str, &str)] = &[
+    ("gh", "GitHub", "https://github.com/search?q={}"),
+    (
+        "w",
+        "Wikipedia",
+        "https://en.wikipedia.org/wiki/Special:Search?search={}",
+    ),
+    ("r", "Reddit", "https://www.reddit.com/search/?q={}"),
+    ("a", "Amazon", "https://www.amazon.com/s?k={}"),
+    ("imdb", "IMDb", "https://www.imdb.com/find?q={}"),
+    (
+        "aw",
+        "ArchWiki",
+        "https://wiki.archlinux.org/index.php?search={}",
+    ),
+    ("mdn", "MDN", "https://developer.mozilla.org/search?q={}"),
+    ("npm", "npm", "https://www.npmjs.com/search?q={}"),
+    ("crates", "crates.io", "https://crates.io/search?q={}"),
+    (
+        "docsrs",
+        "Docs.rs",
+        "https://docs.rs/releases/search?query={}",
+    ),
+    ("pypi", "PyPI", "https://pypi.org/search/?q={}"),
+    ("gl", "GitLab", "https://gitlab.com/search?search={}"),
+];
+
+fn built_in_bangs() -> impl Iterator<Item = SearchEngine> {
+    BUILT_IN_BANGS.iter().map(|(keyword, name, url)| {
+        SearchEngine::new(keyword, name, url, "Web search", 0).with_keywords(&[keyword])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_search_terms() {
+        assert_eq!(
+            fill_template("https://example.com/?q={searchTerms}", "rust lang"),
+            "https://example.com/?q=rust%20lang"
+        );
+        assert_eq!(
+            fill_template("https://example.com/?q={}", "a&b"),
+            "https://example.com/?q=a%26b"
+        );
+    }
+
+    #[test]
+    fn fills_known_parameters() {
+        assert_eq!(
+            fill_template(
+                "https://example.com/?q={searchTerms}&ie={inputEncoding}&hl={language?}",
+                "x"
+            ),
+            "https://example.com/?q=x&ie=UTF-8&hl=*"
+        );
+    }
+
+    #[test]
+    fn empties_optional_and_unknown_parameters() {
+        assert_eq!(
+            fill_template(
+                "https://example.com/?q={searchTerms}&page={startPage?}&x={custom:thing}",
+                "x"
+            ),
+            "https://example.com/?q=x&page=&x="
+        );
+    }
+
+    #[test]
+    fn finds_engine_keywords_and_bangs_without_bang_sign() {
+        let config = EnginesConfig {
+            default: None,
+            engines: built_in_engines(),
+            bangs: built_in_bangs().collect(),
+        };
+        let id = |keyword| {
+            config
+                .by_engine_keyword(keyword)
+                .map(|engine| engine.id.as_str())
+        };
+        assert_eq!(id("yt"), Some("youtube"));
+        assert_eq!(id("GH"), Some("gh"));
+        assert_eq!(id("crates"), Some("crates"));
+        assert_eq!(id("w"), None);
+        assert_eq!(id("nope"), None);
+    }
+
+    #[test]
+    fn appends_new_engines_and_keeps_comments() {
+        let content = "# my engines\ndefault = \"ddg\"\n\n[[engine]]\nid = \"google\"\nenabled = false # too many ads\n";
+        let entry = EngineEntry {
+            id: "arch-wiki".to_string(),
+            url: Some("https://wiki.archlinux.org/?search={searchTerms}".to_string()),
+            ..Default::default()
+        };
+
+        let saved = with_engine(content, &entry).unwrap();
+        assert!(saved.starts_with(content), "{}", saved);
+        let file = toml::from_str::<EnginesFile>(&saved).unwrap();
+        let ids = file
+            .engines
+            .iter()
+            .map(|engine| engine.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["google", "arch-wiki"]);
+        assert_eq!(file.engines[1].url, entry.url);
+    }
+
+    #[test]
+    fn replaces_engine_with_the_same_id() {
+        let content = "[[engine]]\nid = \"a\"\nurl = \"https://old.example.com/?q={}\"\n\n# the second one\n[[engine]]\nid = \"b\"\nurl = \"https://b.example.com/?q={}\"\n";
+        let entry = EngineEntry {
+            id: "b".to_string(),
+            url: Some("https://new.example.com/?q={}".to_string()),
+            ..Default::default()
+        };
+
+        let saved = with_engine(content, &entry).unwrap();
+        assert!(saved.contains("# the second one"), "{}", saved);
+        let file = toml::from_str::<EnginesFile>(&saved).unwrap();
+        assert_eq!(file.engines.len(), 2);
+        assert_eq!(
+            file.engines[0].url.as_deref(),
+            Some("https://old.example.com/?q={}")
+        );
+        assert_eq!(file.engines[1].url, entry.url);
+    }
+
+    #[test]
+    fn starts_an_empty_file() {
+        let entry = EngineEntry {
+            id: "a".to_string(),
+            ..Default::default()
+        };
+        let saved = with_engine("", &entry).unwrap();
+        assert_eq!(saved.trim(), "[[engine]]\nid = \"a\"");
+    }
+
+    #[test]
+    fn keeps_unclosed_braces() {
+        assert_eq!(
+            fill_template("https://example.com/?q={searchTerms}&a={", "x"),
+            "https://example.com/?q=x&a={"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::utils::logger::{LogLevel, Logger};
+use std::path::PathBuf;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("history", LogLevel::Debug);
+}
+
+/// commands executed from run mode, newest first
+///
+/// stored one command per line in `~/.local/share/starlight/history`
+pub struct CommandHistory {
+    path: PathBuf,
+    entries: Vec<String>,
+    max_entries: usize,
+}
+
+impl CommandHistory {
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_default()
+            .join("starlight")
+            .join("history")
+    }
+
+    pub fn load(path: PathBuf, max_entries: usize) -> Self {
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .take(max_entries)
+                .map(|line| line.to_string())
+                .collect(),
+            Err(_) => {
+                LOG.debug(&format!("no history at {}", path.display()));
+                Vec::new()
+            }
+        };
+
+        Self {
+            path,
+            entries,
+            max_entries,
+        }
+    }
+
+    /// record a command, moving it to the top if it was run before
+    pub fn add(&mut self, command: &str) {
+        // a line per entry, so multi line commands are joined
+        let command = command.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+        let command = command.trim();
+        if command.is_empty() || self.max_entries == 0 {
+            return;
+        }
+
+        self.entries.retain(|entry| entry != command);
+        self.entries.insert(0, command.to_string());
+        self.entries.truncate(self.max_entries);
+
+        if let Err(e) = self.save() {
+            LOG.error(&format!(
+                "Failed to save history to {}: {}",
+                self.path.display(),
+                e
+            ));
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        std::fs::write(&self.path, content)
+    }
+
+    /// full command lines containing the query, newest first
+    pub fn search(&self, query: &str) -> Vec<&String> {
+        let query = query.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|entry| entry.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// the nth newest command matching the query, for reverse search recall
+    pub fn recall(&self, query: &str, nth: usize) -> Option<&String> {
+        self.search(query).into_iter().nth(nth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// history file of its own per test, removed again when dropped
+    struct TempHistory(PathBuf);
+
+    impl TempHistory {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("starlight-test-{}-{}", std::process::id(), name))
+                .join("history");
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+            Self(path)
+        }
+
+        fn load(&self, max_entries: usize) -> CommandHistory {
+            CommandHistory::load(self.0.clone(), max_entries)
+        }
+    }
+
+    impl Drop for TempHistory {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(self.0.parent().unwrap());
+        }
+    }
+
+    #[test]
+    fn moves_repeated_commands_to_the_top() {
+        let file = TempHistory::new("dedup");
+        let mut history = file.load(10);
+        history.add("ls");
+        history.add("htop");
+        history.add("ls");
+
+        assert_eq!(history.search(""), vec!["ls", "htop"]);
+    }
+
+    #[test]
+    fn keeps_at_most_max_entries() {
+        let file = TempHistory::new("max");
+        let mut history = file.load(2);
+        for command in ["one", "two", "three"] {
+            history.add(command);
+        }
+
+        assert_eq!(history.search(""), vec!["three", "two"]);
+    }
+
+    #[test]
+    fn ignores_empty_commands_and_joins_lines() {
+        let file = TempHistory::new("lines");
+        let mut history = file.load(10);
+        history.add("   ");
+        history.add("echo one\n  echo two");
+
+        assert_eq!(history.search(""), vec!["echo one echo two"]);
+    }
+
+    #[test]
+    fn recalls_matches_newest_first() {
+        let file = TempHistory::new("recall");
+        let mut history = file.load(10);
+        for command in ["git status", "ls", "git push", "GIT log"] {
+            history.add(command);
+        }
+
+        assert_eq!(
+            history.recall("git", 0).map(String::as_str),
+            Some("GIT log")
+        );
+        assert_eq!(
+            history.recall("git", 1).map(String::as_str),
+            Some("git push")
+        );
+        assert_eq!(
+            history.recall("git", 2).map(String::as_str),
+            Some("git status")
+        );
+        assert_eq!(history.recall("git", 3), None);
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let file = TempHistory::new("persist");
+        let mut history = file.load(10);
+        history.add("first");
+        history.add("second");
+
+        let reloaded = file.load(10);
+        assert_eq!(reloaded.search(""), vec!["second", "first"]);
+
+        let truncated = file.load(1);
+        assert_eq!(truncated.search(""), vec!["second"]);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::utils::completion::expand_path;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+
+/// top level domains recognized without a scheme, like `github.com`
+const TLDS: &[&str] = &[
+    "com", "net", "org", "io", "dev", "app", "edu", "gov", "mil", "int", "info", "biz", "co", "me",
+    "tv", "ai", "gg", "ly", "to", "fm", "xyz", "online", "site", "tech", "store", "blog", "cloud",
+    "page", "wiki", "news", "live", "link", "club", "shop", "top", "art", "design", "space",
+    "website", "social", "eu", "us", "uk", "de", "fr", "nl", "be", "at", "ch", "it", "es", "pt",
+    "se", "no", "fi", "dk", "is", "ie", "cz", "sk", "hu", "ro", "gr", "tr", "ua", "ru", "lt", "lv",
+    "ee", "ca", "mx", "br", "ar", "cl", "au", "nz", "jp", "cn", "kr", "tw", "hk", "in", "id", "vn",
+    "th", "my", "sg", "ph", "il", "za", "np", "local", "lan", "arpa",
+];
+
+/// top level domains that are also common file extensions, `main.rs` is a
+/// file while `docs.rs/serde` is a url, so these need a path or a port
+const FILE_EXTENSION_TLDS: &[&str] = &["rs", "md", "py", "sh", "pl", "so", "cc", "zip", "mov"];
+
+/// something typed that can be opened directly instead of searched
+#[derive(Debug, Clone, PartialEq)]
+pub enum Link {
+    /// url with its scheme, added when it was left out
+    Url(String),
+    /// existing file or directory
+    Path(PathBuf),
+}
+
+impl Link {
+    /// url or path to show to the user
+    pub fn target(&self) -> String {
+        match self {
+            Link::Url(url) => url.clone(),
+            Link::Path(path) => path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+/// detect urls, bare domains, `ip:port`, `file://` urls and existing local paths
+pub fn detect_link(query: &str) -> Option<Link> {
+    let query = query.trim();
+    if query.is_empty() || query.contains(char::is_whitespace) {
+        return None;
+    }
+
+    if let Some(path) = query.strip_prefix("file://") {
+        let path = urlencoding::decode(path).ok()?;
+        return Some(Link::Path(PathBuf::from(path.as_ref())));
+    }
+    if has_scheme(query) {
+        return Some(Link::Url(query.to_string()));
+    }
+
+    if ["/", "~", "./", "../"]
+        .iter()
+        .any(|prefix| query.starts_with(prefix))
+    {
+        let path = expand_path(query);
+        return path.exists().then_some(Link::Path(path));
+    }
+
+    detect_host(query)
+}
+
+/// `scheme://rest`, the scheme made of letters, digits, `+`, `-` and `.`
+fn has_scheme(query: &str) -> bool {
+    let Some((scheme, rest)) = query.split_once("://") else {
+        return false;
+    };
+    !rest.is_empty()
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// url of a bare host with an optional port and path
+///
+/// local hosts and ip addresses get `http://`, domains get `https://`
+fn detect_host(query: &str) -> Option<Link> {
+    let host_end = query.find(['/', '?', '#']).unwrap_or(query.len());
+    let (authority, path) = query.split_at(host_end);
+
+    let (host, port) = match authority.rsplit_once(':') {
+        // the colons of an ipv6 address are inside the brackets
+        Some((host, port)) if !port.contains(']') => (host, Some(port)),
+        _ => (authority, None),
+    };
+    if port.is_some_and(|port| port.parse::<u16>().is_err()) {
+        return None;
+    }
+
+    let local = host.eq_ignore_ascii_case("localhost")
+        || host.parse::<Ipv4Addr>().is_ok()
+        || host
+            .strip_prefix('[')
+            .and_then(|host| host.strip_suffix(']'))
+            .is_some_and(|host| host.parse::<Ipv6Addr>().is_ok());
+    if local {
+        return Some(Link::Url(format!("http://{}", query)));
+    }
+
+    is_domain(host, port.is_some() || !path.is_empty())
+        .then(|| Link::Url(format!("https://{}", query)))
+}
+
+fn is_domain(host: &str, has_port_or_path: bool) -> bool {
+    let labels = host.split('.').collect::<Vec<_>>();
+    let valid_labels = labels.iter().all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+    if labels.len() < 2 || !valid_labels {
+        return false;
+    }
+
+    let tld = labels[labels.len() - 1].to_lowercase();
+    TLDS.contains(&tld.as_str())
+        || (FILE_EXTENSION_TLDS.contains(&tld.as_str())
+            && (has_port_or_path || labels[0].eq_ignore_ascii_case("www")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(query: &str) -> Option<String> {
+        match detect_link(query)? {
+            Link::Url(url) => Some(url),
+            Link::Path(_) => None,
+        }
+    }
+
+    #[test]
+    fn keeps_urls_with_a_scheme() {
+        assert_eq!(
+            url("https://example.com/a?b=c").as_deref(),
+            Some("https://example.com/a?b=c")
+        );
+        assert_eq!(url("ftp://files.lan").as_deref(), Some("ftp://files.lan"));
+        assert_eq!(
+            url("git+ssh://host/repo").as_deref(),
+            Some("git+ssh://host/repo")
+        );
+        assert_eq!(url("https://"), None);
+        assert_eq!(url("1http://host"), None);
+    }
+
+    #[test]
+    fn adds_https_to_domains() {
+        assert_eq!(url("github.com").as_deref(), Some("https://github.com"));
+        assert_eq!(
+            url("Example.ORG/path#top").as_deref(),
+            Some("https://Example.ORG/path#top")
+        );
+        assert_eq!(
+            url("sub.example.co:8443").as_deref(),
+            Some("https://sub.example.co:8443")
+        );
+    }
+
+    #[test]
+    fn adds_http_to_local_hosts() {
+        assert_eq!(
+            url("localhost:8080").as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(
+            url("192.168.1.1/admin").as_deref(),
+            Some("http://192.168.1.1/admin")
+        );
+        assert_eq!(url("[::1]:3000").as_deref(), Some("http://[::1]:3000"));
+    }
+
+    #[test]
+    fn file_extensions_need_a_path_or_port() {
+        assert_eq!(url("main.rs"), None);
+        assert_eq!(url("README.md"), None);
+        assert_eq!(
+            url("docs.rs/serde").as_deref(),
+            Some("https://docs.rs/serde")
+        );
+        assert_eq!(
+            url("www.example.sh").as_deref(),
+            Some("https://www.example.sh")
+        );
+    }
+
+    #[test]
+    fn ignores_plain_text() {
+        for query in [
+            "",
+            "hello",
+            "hello world",
+            "github.com is down",
+            "3.14",
+            "example.unknowntld",
+            "-bad-.com",
+            "host:99999",
+            "a..com",
+        ] {
+            assert_eq!(detect_link(query), None, "{}", query);
+        }
+    }
+
+    #[test]
+    fn detects_existing_paths() {
+        assert_eq!(detect_link("/"), Some(Link::Path(PathBuf::from("/"))));
+        assert_eq!(detect_link("/starlight/missing/path"), None);
+        assert_eq!(
+            detect_link("file:///tmp/my%20file.txt"),
+            Some(Link::Path(PathBuf::from("/tmp/my file.txt")))
+        );
+    }
+
+    #[test]
+    fn shows_the_target() {
+        assert_eq!(
+            Link::Url("https://example.com".to_string()).target(),
+            "https://example.com"
+        );
+        assert_eq!(Link::Path(PathBuf::from("/etc")).target(), "/etc");
+    }
+}
@@ -0,0 +1,115 @@
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    shell::login_env,
+};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("manpages", LogLevel::Debug);
+}
+
+const APROPOS_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_QUERY_LENGTH: usize = 3;
+
+/// one line descriptions of commands from the man page database
+#[derive(Debug, Default)]
+pub struct ManIndex {
+    // description by command name
+    descriptions: HashMap<String, String>,
+}
+
+impl ManIndex {
+    /// read the whatis database through `apropos`
+    ///
+    /// only user commands (1), games (6) and admin commands (8) are kept,
+    /// the other sections describe things that can't be run
+    pub async fn load() -> Self {
+        let mut cmd = tokio::process::Command::new("apropos");
+        if let Some(env) = login_env() {
+            cmd.envs(env);
+        }
+        cmd.arg(".")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+
+        let output = match tokio::time::timeout(APROPOS_TIMEOUT, cmd.output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => {
+                LOG.warn(&format!("Failed to read the man page database: {}", e));
+                return Self::default();
+            }
+            Err(_) => {
+                LOG.warn("reading the man page database timed out");
+                return Self::default();
+            }
+        };
+
+        let index = Self::parse(&String::from_utf8_lossy(&output.stdout));
+        LOG.debug(&format!(
+            "found {} command descriptions",
+            index.descriptions.len()
+        ));
+        index
+    }
+
+    /// parse `apropos` lines of man-db (`ls (1) - list...`) and mandoc
+    /// (`gzip, gunzip(1) - compress...`)
+    fn parse(content: &str) -> Self {
+        let mut descriptions = HashMap::new();
+
+        for line in content.lines() {
+            let Some((names, description)) = line.split_once(" - ") else {
+                continue;
+            };
+            let Some((names, section)) = names.trim_end().rsplit_once('(') else {
+                continue;
+            };
+            if !section.starts_with(['1', '6', '8']) {
+                continue;
+            }
+
+            for name in names.split(',') {
+                // man-db puts the section after every name
+                let name = name.split_whitespace().next().unwrap_or_default();
+                if !name.is_empty() {
+                    descriptions
+                        .entry(name.to_string())
+                        .or_insert_with(|| description.trim().to_string());
+                }
+            }
+        }
+
+        Self { descriptions }
+    }
+
+    pub fn description(&self, name: &str) -> Option<&str> {
+        self.descriptions.get(name).map(String::as_str)
+    }
+
+    /// commands whose description contains every word of the query
+    pub fn search_descriptions(&self, query: &str) -> Vec<&str> {
+        // a letter or two would match almost everything
+        if query.trim().len() < MIN_QUERY_LENGTH {
+            return Vec::new();
+        }
+
+        let words = query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        let mut names = self
+            .descriptions
+            .iter()
+            .filter(|(_, description)| {
+                let description = description.to_lowercase();
+                words.iter().all(|word| description.contains(word.as_str()))
+            })
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
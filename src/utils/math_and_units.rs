@@ -1,7 +1,9 @@
+use crate::utils::units::{Dimension, Quantity, Unit, UnitError, find_unit, readable_unit};
 use exmex::Express;
-use gtk::{prelude::*};
-use uom::si::f64::*;
-use uom::si::length::{kilometer, meter, mile};
+use gtk::prelude::*;
+
+/// words between the unit and the units it's converted to
+const SEPARATORS: &[&str] = &["to", "in", "as", "->"];
 
 pub fn try_math_expression(query: &str) -> Option<(String, &'static str)> {
     if let Ok(expr) = exmex::parse::<f64>(query) {
@@ -15,61 +17,378 @@ pub fn try_math_expression(query: &str) -> Option<(String, &'static str)> {
     }
 }
 
-pub fn try_unit_conversion(query: &str) -> Option<(String, &'static str)> {
-    let lower = query.to_lowercase();
-    if lower.contains("to") {
-        let parts: Vec<&str> = lower.split("to").map(|s| s.trim()).collect();
+/// calculate with quantities and convert them, like `3 km + 250 m in mi`,
+/// `60 mph * 2.5 h`, `5'10" to cm` or `1.5 GiB / 20 MB/s`
+///
+/// `in`, `as` and `->` work like `to` and more targets can be chained, without
+/// one the result gets a readable unit. Adding or converting quantities of
+/// different dimensions gives an error
+pub fn try_unit_conversion(query: &str) -> Option<Result<(String, &'static str), UnitError>> {
+    let query = query.trim();
+    let mut parser = Parser::new(query);
+    let quantity = parser.expression();
+    let expression = query[..parser.position].trim();
 
-        if parts.len() >= 2 {
-            let value_parts: Vec<&str> = parts[0].split_whitespace().collect();
-            if value_parts.len() != 2 {
-                return None;
+    let rest = query[parser.position..]
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let targets = match rest.split_first() {
+        None => Vec::new(),
+        Some((separator, targets)) if SEPARATORS.contains(separator) => target_units(targets)?,
+        Some(_) => return None,
+    };
+    // plain numbers are left to `try_math_expression`, a lone `5 km` to the search
+    let single = parser.quantities == 1;
+    if parser.units.is_empty() || (single && targets.is_empty()) {
+        return None;
+    }
+
+    let quantity = match quantity {
+        Ok(quantity) => quantity,
+        Err(EvalError::Invalid) => return None,
+        Err(EvalError::Unit(e)) => return Some(Err(e)),
+    };
+    if !quantity.value.is_finite() {
+        return None;
+    }
+
+    let mut result_str = if single {
+        // as converted from, `5 kilometres to mi` shows `5 km = ...`
+        let unit = parser.units[0];
+        format!(
+            "{} {}",
+            format_value(unit.from_base(quantity.value)),
+            unit.symbol
+        )
+    } else {
+        expression.to_string()
+    };
+    if targets.is_empty() {
+        result_str.push_str(&format!(" = {}", format_quantity(&quantity, &parser.units)));
+    }
+    for target in targets {
+        let value = match quantity.value_in(target) {
+            Ok(value) => value,
+            Err(_) if single => {
+                return Some(Err(UnitError::Incompatible {
+                    from: parser.units[0],
+                    to: target,
+                }));
             }
+            Err(e) => return Some(Err(e)),
+        };
+        result_str.push_str(&format!(" = {} {}", format_value(value), target.symbol));
+    }
+
+    Some(Ok((result_str, "accessories-calculator")))
+}
+
+/// the quantity in a readable unit, or in base units for one without a name
+fn format_quantity(quantity: &Quantity, typed: &[&'static Unit]) -> String {
+    match readable_unit(quantity, typed) {
+        Some(unit) => format!(
+            "{} {}",
+            format_value(unit.from_base(quantity.value)),
+            unit.symbol
+        ),
+        None if quantity.dimension == Dimension::NONE => format_value(quantity.value),
+        None => format!("{} {}", format_value(quantity.value), quantity.dimension),
+    }
+}
+
+enum EvalError {
+    /// not an expression with units, so probably something to search
+    Invalid,
+    Unit(UnitError),
+}
+
+impl From<UnitError> for EvalError {
+    fn from(e: UnitError) -> Self {
+        EvalError::Unit(e)
+    }
+}
+
+/// recursive descent over an expression of numbers with units
+///
+/// quantities written next to each other are added, so `5'10"` is `5 ft + 10 in`
+/// and `1 h 30 min` is `1 h + 30 min`
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    /// units in the order they were typed
+    units: Vec<&'static Unit>,
+    /// numbers and parentheses in the expression
+    quantities: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            position: 0,
+            units: Vec::new(),
+            quantities: 0,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
 
-            let mut value = match value_parts[0].parse::<f64>() {
-                Ok(v) => v,
-                Err(_) => return None,
+    /// the next character after whitespace, if it's one of the operators
+    fn operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        // `->` converts like `to`, it's no minus
+        if self.rest().starts_with("->") {
+            return None;
+        }
+        let next = self
+            .rest()
+            .chars()
+            .next()
+            .filter(|c| operators.contains(c))?;
+        self.position += next.len_utf8();
+        Some(next)
+    }
+
+    fn expression(&mut self) -> Result<Quantity, EvalError> {
+        let mut quantity = self.term()?;
+        while let Some(operator) = self.operator(&['+', '-']) {
+            let right = self.term()?;
+            quantity = match operator {
+                '+' => quantity.checked_add(right)?,
+                _ => quantity.checked_sub(right)?,
+            };
+        }
+        Ok(quantity)
+    }
+
+    fn term(&mut self) -> Result<Quantity, EvalError> {
+        let mut quantity = self.power()?;
+        while let Some(operator) = self.operator(&['*', '×', '/', '÷']) {
+            let right = self.power()?;
+            quantity = match operator {
+                '*' | '×' => quantity.checked_mul(right)?,
+                _ => quantity.checked_div(right)?,
             };
-            let mut current_unit = value_parts[1];
-
-            let mut result_str = format!("{:.4} {}", value, current_unit);
-
-            for target_unit in &parts[1..] {
-                let target_unit = target_unit.trim();
-                value = match (current_unit, target_unit) {
-
-                    // length
-                    ("km", "mi") => Length::new::<kilometer>(value).get::<mile>(),
-                    ("mi", "km") => Length::new::<mile>(value).get::<kilometer>(),
-                    ("km", "m")  => Length::new::<kilometer>(value).get::<meter>(),
-                    ("m", "km")  => Length::new::<meter>(value).get::<kilometer>(),
-
-                    // temperature
-                    ("c", "f") => (value * 9.0 / 5.0) + 32.0,
-                    ("f", "c") => (value - 32.0) * 5.0 / 9.0,
-                    ("c", "k") => value + 273.15,
-                    ("k", "c") => value - 273.15,
-                    ("f", "k") => ((value - 32.0) * 5.0 / 9.0) + 273.15,
-                    ("k", "f") => ((value - 273.15) * 9.0 / 5.0) + 32.0,
-
-                    // storage (binary units, 1024)
-                    ("gb", "mb") => value * 1024.0,
-                    ("mb", "gb") => value / 1024.0,
-                    ("mb", "kb") => value * 1024.0,
-                    ("kb", "mb") => value / 1024.0,
-                    ("gb", "kb") => value * 1024.0 * 1024.0,
-                    ("kb", "gb") => value / 1024.0 / 1024.0,
-
-                    _ => return None,
-                };
-                current_unit = target_unit;
-                result_str.push_str(&format!(" = {} {}", trim_trailing_zeros(value), current_unit));
+        }
+        Ok(quantity)
+    }
+
+    fn power(&mut self) -> Result<Quantity, EvalError> {
+        let base = self.unary()?;
+        if self.operator(&['^']).is_none() {
+            return Ok(base);
+        }
+        let exponent = self.unary()?;
+        if exponent.dimension != Dimension::NONE {
+            return Err(EvalError::Invalid);
+        }
+        if base.dimension == Dimension::NONE {
+            return Ok(Quantity::number(base.value.powf(exponent.value)));
+        }
+        // only whole powers of a unit make sense, like `(3 m)^2`
+        if exponent.value.fract() != 0.0 || exponent.value.abs() > f64::from(i8::MAX) {
+            return Err(EvalError::Invalid);
+        }
+        Ok(base.checked_powi(exponent.value as i8)?)
+    }
+
+    fn unary(&mut self) -> Result<Quantity, EvalError> {
+        match self.operator(&['-', '+']) {
+            // the sign of a number goes before its unit, `-5 °C` isn't `-(5 °C)`
+            Some('-') if self.at_number() => self.sum(-1.0),
+            Some('-') => Ok(self.unary()?.checked_mul(Quantity::number(-1.0))?),
+            Some(_) => self.unary(),
+            None => self.sum(1.0),
+        }
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest()
+            .starts_with(|c: char| c.is_ascii_digit() || c == '.')
+    }
+
+    /// quantities with units next to each other, like `5 ft 10 in`
+    fn sum(&mut self, sign: f64) -> Result<Quantity, EvalError> {
+        let (mut quantity, mut has_unit) = self.primary(sign)?;
+        loop {
+            if !self.at_number() {
+                return Ok(quantity);
+            }
+            let (next, next_has_unit) = self.primary(sign)?;
+            if !has_unit || !next_has_unit {
+                return Err(EvalError::Invalid);
+            }
+            quantity = quantity.checked_add(next)?;
+            has_unit = next_has_unit;
+        }
+    }
+
+    /// a number or an expression in parentheses, with an optional unit
+    fn primary(&mut self, sign: f64) -> Result<(Quantity, bool), EvalError> {
+        self.skip_whitespace();
+        self.quantities += 1;
+        let (quantity, after_number) = if self.operator(&['(']).is_some() {
+            let mut quantity = self.expression()?;
+            self.operator(&[')']).ok_or(EvalError::Invalid)?;
+            if sign < 0.0 {
+                quantity = quantity.checked_mul(Quantity::number(sign))?;
+            }
+            (quantity, false)
+        } else if self.input[..self.position].ends_with('/')
+            && !self
+                .rest()
+                .starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        {
+            // the unit after the `/` of a compound unit, like in `kg/m3`
+            (Quantity::number(sign), true)
+        } else {
+            (Quantity::number(sign * self.number()?), true)
+        };
+
+        let Some(unit) = self.unit(after_number) else {
+            return Ok((quantity, false));
+        };
+        if quantity.dimension != Dimension::NONE {
+            return Err(EvalError::Invalid);
+        }
+        // a power right after the unit only applies to it, like in `9.81 m/s^2`
+        match self.unit_power() {
+            Some(power) => {
+                let unit = Quantity::new(1.0, unit).checked_powi(power)?;
+                Ok((unit.checked_mul(quantity)?, true))
+            }
+            None => Ok((Quantity::new(quantity.value, unit), true)),
+        }
+    }
+
+    /// `^2` or `^-1` directly after a unit
+    fn unit_power(&mut self) -> Option<i8> {
+        let rest = self.rest().strip_prefix('^')?;
+        let digits = rest.strip_prefix('-').unwrap_or(rest);
+        let end = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(digits.len());
+        let length = rest.len() - digits.len() + end;
+        let power = rest[..length].parse::<i8>().ok()?;
+        self.position += 1 + length;
+        Some(power)
+    }
+
+    fn number(&mut self) -> Result<f64, EvalError> {
+        let rest = self.rest();
+        let mut end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        // an exponent needs digits, `5em` isn't one
+        let exponent = rest[end..]
+            .strip_prefix(['e', 'E'])
+            .map(|exponent| exponent.strip_prefix(['+', '-']).unwrap_or(exponent));
+        if let Some(digits) =
+            exponent.filter(|digits| digits.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let digits_end = digits
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(digits.len());
+            end = rest.len() - digits.len() + digits_end;
+        }
+
+        let value = rest[..end].parse::<f64>().map_err(|_| EvalError::Invalid)?;
+        self.position += end;
+        Ok(value)
+    }
+
+    /// the longest unit name at the position, it may span words like `fl oz`
+    ///
+    /// `to` and the other separators only count as a unit after a number, so
+    /// `5 in` is in inches while `(2 + 3) in cm` converts
+    fn unit(&mut self, after_number: bool) -> Option<&'static Unit> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let first = unit_word_len(rest);
+        if first == 0 {
+            return None;
+        }
+
+        // the ends of up to three words, then the shorter parts of the first one
+        let mut ends = vec![first];
+        let mut end = first;
+        for _ in 0..2 {
+            let after = &rest[end..];
+            let spaces = after.len() - after.trim_start().len();
+            let word = unit_word_len(&after[spaces..]);
+            if spaces == 0 || word == 0 {
+                break;
             }
+            end += spaces + word;
+            ends.push(end);
+        }
+        ends.reverse();
+        // a unit is only cut off before a `/` or `^`, so `km/2` is `km / 2`
+        let mut prefixes = rest[..first]
+            .char_indices()
+            .skip(1)
+            .filter(|(_, c)| matches!(c, '/' | '^'))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        prefixes.reverse();
+        ends.extend(prefixes);
+
+        let (end, unit) = ends.into_iter().find_map(|end| {
+            let name = &rest[..end];
+            if !after_number && SEPARATORS.contains(&name) {
+                return None;
+            }
+            // the power belongs to the last unit, `m/s^2` is `m / s^2`
+            if name.contains('/') && rest[end..].starts_with('^') {
+                return None;
+            }
+            find_unit(name).map(|unit| (end, unit))
+        })?;
+        self.position += end;
+        self.units.push(unit);
+        Some(unit)
+    }
+}
 
-            return Some((result_str, "accessories-calculator"));
+/// length of the unit name at the start, letters and symbols like `°`, `/`
+/// and `'`, digits only after a letter like in `m2`
+fn unit_word_len(text: &str) -> usize {
+    let mut previous = None;
+    for (index, c) in text.char_indices() {
+        let symbol = c.is_alphabetic() || "°º'\"′″²³/^℃℉".contains(c);
+        let digit =
+            c.is_ascii_digit() && previous.is_some_and(|p: char| p.is_alphabetic() || p == '^');
+        if !symbol && !digit {
+            return index;
         }
+        previous = Some(c);
     }
-    None
+    text.len()
+}
+
+/// units separated by `to`, all of them known
+fn target_units(words: &[&str]) -> Option<Vec<&'static Unit>> {
+    let mut units = Vec::new();
+    let mut start = 0;
+    for (index, word) in words.iter().enumerate() {
+        if SEPARATORS.contains(word) && index > start {
+            // `in` is a separator after a unit, but can also be the unit
+            if let Some(unit) = find_unit(&words[start..index].join(" ")) {
+                units.push(unit);
+                start = index + 1;
+            }
+        }
+    }
+    units.push(find_unit(&words.get(start..)?.join(" "))?);
+    Some(units)
 }
 
 pub fn copy_to_clipboard(text: &str) {
@@ -79,10 +398,129 @@ pub fn copy_to_clipboard(text: &str) {
     }
 }
 
+/// the value with at most 4 decimals, tiny and huge ones in scientific notation
+fn format_value(num: f64) -> String {
+    let magnitude = num.abs();
+    if magnitude != 0.0 && !(1e-4..1e15).contains(&magnitude) {
+        let scientific = format!("{:.4e}", num);
+        let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+        return format!(
+            "{}e{}",
+            mantissa.trim_end_matches('0').trim_end_matches('.'),
+            exponent
+        );
+    }
+    trim_trailing_zeros(num)
+}
+
 fn trim_trailing_zeros(num: f64) -> String {
     if num.fract() == 0.0 {
         format!("{}", num as i64)
     } else {
-        format!("{:.4}", num).trim_end_matches('0').trim_end_matches('.').to_string()
+        format!("{:.4}", num)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(query: &str) -> Option<Result<String, String>> {
+        try_unit_conversion(query)
+            .map(|result| result.map(|(text, _)| text).map_err(|e| e.to_string()))
+    }
+
+    fn ok(text: &str) -> Option<Result<String, String>> {
+        Some(Ok(text.to_string()))
+    }
+
+    fn err(text: &str) -> Option<Result<String, String>> {
+        Some(Err(text.to_string()))
+    }
+
+    #[test]
+    fn converts_a_quantity() {
+        assert_eq!(convert("10 km to mi"), ok("10 km = 6.2137 mi"));
+        assert_eq!(convert("5 kilometres -> mi"), ok("5 km = 3.1069 mi"));
+        assert_eq!(convert("1 in in cm"), ok("1 in = 2.54 cm"));
+        assert_eq!(
+            convert("1 GiB to MiB to MB"),
+            ok("1 GiB = 1024 MiB = 1073.7418 MB")
+        );
+    }
+
+    #[test]
+    fn converts_temperatures() {
+        assert_eq!(convert("212 °F to °C"), ok("212 °F = 100 °C"));
+        assert_eq!(convert("32 °F to °C"), ok("32 °F = 0 °C"));
+        assert_eq!(convert("-5 °C to °F"), ok("-5 °C = 23 °F"));
+    }
+
+    #[test]
+    fn calculates_with_units() {
+        assert_eq!(
+            convert("3 km + 250 m in mi"),
+            ok("3 km + 250 m = 2.0195 mi")
+        );
+        assert_eq!(convert("60 mph * 2.5 h"), ok("60 mph * 2.5 h = 150 mi"));
+        assert_eq!(convert("1 GiB + 512 MiB"), ok("1 GiB + 512 MiB = 1.5 GiB"));
+        assert_eq!(convert("(3 m)^2 to ft2"), ok("(3 m)^2 = 96.8752 ft²"));
+        assert_eq!(convert("10 km / 2 km"), ok("10 km / 2 km = 5"));
+    }
+
+    #[test]
+    fn reads_compound_units() {
+        assert_eq!(
+            convert("1.5 GiB / 20 MB/s"),
+            ok("1.5 GiB / 20 MB/s = 80.5306 s")
+        );
+        assert_eq!(convert("10km/2h"), ok("10km/2h = 5 km/h"));
+        assert_eq!(
+            convert("3 kg * 9.81 m/s^2"),
+            ok("3 kg * 9.81 m/s^2 = 29.43 m·kg·s^-2")
+        );
+        assert_eq!(convert("1000 kg/m3 * 2 L"), ok("1000 kg/m3 * 2 L = 2 kg"));
+    }
+
+    #[test]
+    fn adds_quantities_next_to_each_other() {
+        assert_eq!(convert("5'10\" to cm"), ok("5'10\" = 177.8 cm"));
+        assert_eq!(convert("1 h 30 min"), ok("1 h 30 min = 90 min"));
+    }
+
+    #[test]
+    fn reports_dimension_mismatches() {
+        assert_eq!(convert("5 km to kg"), err("km is a length, kg is a mass"));
+        assert_eq!(convert("3 km + 2 kg"), err("a length and a mass"));
+        assert_eq!(convert("5 km + 3"), err("a length and a number"));
+    }
+
+    #[test]
+    fn refuses_arithmetic_on_offset_units() {
+        let message = "°C is a point on a scale, calculate with K instead";
+        assert_eq!(convert("10 °C + 5 °C"), err(message));
+        assert_eq!(convert("20 °C * 2"), err(message));
+        assert_eq!(convert("10 K + 5 K"), ok("10 K + 5 K = 15 K"));
+    }
+
+    #[test]
+    fn leaves_other_queries_alone() {
+        assert_eq!(convert("5 km"), None);
+        assert_eq!(convert("2 + 2"), None);
+        assert_eq!(convert("5 stars"), None);
+        assert_eq!(convert("5 km to home"), None);
+        assert_eq!(convert("2 to 3"), None);
+    }
+
+    #[test]
+    fn formats_values() {
+        assert_eq!(format_value(1.5), "1.5");
+        assert_eq!(format_value(2.0), "2");
+        assert_eq!(format_value(1.23456), "1.2346");
+        assert_eq!(format_value(9.4607e15), "9.4607e15");
+        assert_eq!(format_value(0.00001), "1e-5");
     }
 }
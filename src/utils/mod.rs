@@ -1,5 +1,25 @@
 pub mod logger;
+pub mod aliases;
 pub mod applications;
+pub mod bookmarks;
+pub mod browser;
+pub mod browser_history;
 pub mod command;
+pub mod completion;
+pub mod config;
+pub mod elevate;
+pub mod engines;
+pub mod history;
+pub mod links;
+pub mod manpages;
 pub mod math_and_units;
+pub mod opensearch;
+pub mod processes;
+pub mod safeguard;
+pub mod scripts;
+pub mod search;
+pub mod shell;
+pub mod suggestions;
+pub mod terminal;
+pub mod units;
 pub mod web;
\ No newline at end of file
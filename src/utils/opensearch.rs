@@ -0,0 +1,343 @@
+use crate::utils::{
+    completion::expand_path,
+    config::Config,
+    engines::{EngineEntry, EnginesConfig, EnginesFile},
+    logger::{LogLevel, Logger},
+};
+use base64::Engine;
+use std::path::PathBuf;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("opensearch", LogLevel::Debug);
+}
+
+const SUGGESTIONS_TYPE: &str = "application/x-suggestions+json";
+
+#[derive(Debug)]
+pub enum ImportError {
+    Read(std::io::Error),
+    Download(reqwest::Error),
+    /// not an OpenSearch description, or it has no usable search url
+    Invalid(String),
+    Save(Box<dyn std::error::Error>),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Read(e) => write!(f, "failed to read the description: {}", e),
+            ImportError::Download(e) => write!(f, "failed to download the description: {}", e),
+            ImportError::Invalid(reason) => write!(f, "invalid OpenSearch description: {}", reason),
+            ImportError::Save(e) => write!(f, "failed to save the engine: {}", e),
+        }
+    }
+}
+
+/// search engine read from an OpenSearch description
+#[derive(Debug, Clone, PartialEq)]
+pub struct Description {
+    pub short_name: String,
+    pub description: Option<String>,
+    /// search url template, e.g. `https://example.com/?q={searchTerms}`
+    pub url: String,
+    pub suggest_url: Option<String>,
+    /// url or `data:` uri of the largest image
+    pub image: Option<String>,
+}
+
+impl Description {
+    /// engine id made of the short name, `Arch Wiki (en)` becomes `arch-wiki-en`
+    pub fn id(&self) -> String {
+        let id = self
+            .short_name
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        if id.is_empty() {
+            "engine".to_string()
+        } else {
+            id
+        }
+    }
+}
+
+/// read the OpenSearch description XML
+pub fn parse_description(xml: &str) -> Result<Description, ImportError> {
+    let document =
+        roxmltree::Document::parse(xml).map_err(|e| ImportError::Invalid(e.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "OpenSearchDescription" {
+        return Err(ImportError::Invalid(format!(
+            "expected OpenSearchDescription, found {}",
+            root.tag_name().name()
+        )));
+    }
+
+    let text = |name: &str| {
+        root.children()
+            .find(|node| node.tag_name().name() == name)
+            .and_then(|node| node.text())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(String::from)
+    };
+    let short_name = text("ShortName").ok_or(ImportError::Invalid("no ShortName".to_string()))?;
+
+    let mut url = None;
+    let mut suggest_url = None;
+    for node in root
+        .children()
+        .filter(|node| node.tag_name().name() == "Url")
+    {
+        let Some(template) = url_template(node) else {
+            continue;
+        };
+        match node.attribute("type").unwrap_or("text/html") {
+            "text/html" if url.is_none() => url = Some(template),
+            SUGGESTIONS_TYPE if suggest_url.is_none() => suggest_url = Some(template),
+            _ => {}
+        }
+    }
+    let url = url.ok_or(ImportError::Invalid("no text/html search url".to_string()))?;
+
+    // the largest image, most descriptions only have a 16x16 favicon
+    let image = root
+        .children()
+        .filter(|node| node.tag_name().name() == "Image")
+        .filter_map(|node| {
+            let width = node.attribute("width").and_then(|w| w.parse::<u32>().ok());
+            Some((width.unwrap_or(0), node.text()?.trim().to_string()))
+        })
+        .filter(|(_, image)| !image.is_empty())
+        .max_by_key(|(width, _)| *width)
+        .map(|(_, image)| image);
+
+    Ok(Description {
+        short_name,
+        description: text("Description"),
+        url,
+        suggest_url,
+        image,
+    })
+}
+
+/// template of a `GET` url with its `Param` children added to the query
+fn url_template(node: roxmltree::Node) -> Option<String> {
+    let method = node.attribute("method").unwrap_or("get");
+    if !method.eq_ignore_ascii_case("get") {
+        return None;
+    }
+
+    let mut template = node.attribute("template")?.trim().to_string();
+    for param in node
+        .children()
+        .filter(|node| node.tag_name().name() == "Param")
+    {
+        let (Some(name), Some(value)) = (param.attribute("name"), param.attribute("value")) else {
+            continue;
+        };
+        let separator = if template.contains('?') { '&' } else { '?' };
+        template.push_str(&format!("{}{}={}", separator, name, value));
+    }
+    (!template.is_empty()).then_some(template)
+}
+
+/// add the engine described by the file or url to `engines.toml`
+///
+/// an engine with the same id is replaced, so importing again updates it
+pub async fn import(source: &str) -> Result<EngineEntry, ImportError> {
+    let xml = if source.starts_with("http://") || source.starts_with("https://") {
+        download(source)
+            .await
+            .map(|body| String::from_utf8_lossy(&body).to_string())
+            .map_err(ImportError::Download)?
+    } else {
+        std::fs::read_to_string(expand_path(source)).map_err(ImportError::Read)?
+    };
+    let description = parse_description(&xml)?;
+    let id = description.id();
+    LOG.debug(&format!("importing engine '{}' from {}", id, source));
+
+    let icon = match &description.image {
+        Some(image) => save_image(&id, image).await,
+        None => None,
+    };
+    let entry = EngineEntry {
+        id,
+        name: Some(description.short_name),
+        url: Some(description.url),
+        suggest_url: description.suggest_url,
+        description: description.description,
+        icon: icon.map(|icon| icon.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+
+    EnginesFile::save_engine(&EnginesConfig::path(), &entry).map_err(ImportError::Save)?;
+    Ok(entry)
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, reqwest::Error> {
+    let response = reqwest::get(url).await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// store the image next to the config, a missing icon doesn't stop the import
+async fn save_image(id: &str, image: &str) -> Option<PathBuf> {
+    let (mime, data) = match image.strip_prefix("data:") {
+        Some(uri) => decode_data_uri(uri)?,
+        None => {
+            let data = download(image)
+                .await
+                .map_err(|e| LOG.warn(&format!("Failed to download {}: {}", image, e)))
+                .ok()?;
+            (format!("image/{}", url_extension(image)), data)
+        }
+    };
+    let extension = match mime.as_str() {
+        "image/png" => "png",
+        "image/svg+xml" | "image/svg" => "svg",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        _ => "ico",
+    };
+
+    let path = Config::dir()
+        .join("icons")
+        .join(format!("{}.{}", id, extension));
+    let saved = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, data));
+    match saved {
+        Ok(()) => Some(path),
+        Err(e) => {
+            LOG.warn(&format!("Failed to save {}: {}", path.display(), e));
+            None
+        }
+    }
+}
+
+/// extension of the file the url points to, `favicon.png?v=2` gives `png`
+fn url_extension(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default()
+}
+
+/// mime type and data of `image/png;base64,...`
+fn decode_data_uri(uri: &str) -> Option<(String, Vec<u8>)> {
+    let (header, data) = uri.split_once(',')?;
+    let mime = header.split(';').next().unwrap_or_default().to_lowercase();
+    let data = if header.ends_with(";base64") {
+        base64::engine::general_purpose::STANDARD
+            .decode(data.split_whitespace().collect::<String>())
+            .ok()?
+    } else {
+        urlencoding::decode_binary(data.as_bytes()).to_vec()
+    };
+    Some((mime, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Arch Wiki (en)</ShortName>
+  <Description>Search the Arch Wiki</Description>
+  <Url type="text/html" template="https://wiki.archlinux.org/?search={searchTerms}"/>
+</OpenSearchDescription>"#;
+
+    #[test]
+    fn parses_minimal_description() {
+        let description = parse_description(MINIMAL).unwrap();
+
+        assert_eq!(description.short_name, "Arch Wiki (en)");
+        assert_eq!(description.id(), "arch-wiki-en");
+        assert_eq!(
+            description.description.as_deref(),
+            Some("Search the Arch Wiki")
+        );
+        assert_eq!(
+            description.url,
+            "https://wiki.archlinux.org/?search={searchTerms}"
+        );
+        assert_eq!(description.suggest_url, None);
+        assert_eq!(description.image, None);
+    }
+
+    #[test]
+    fn appends_params_to_the_template() {
+        let xml = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Example</ShortName>
+  <Url type="text/html" method="get" template="https://example.com/search">
+    <Param name="q" value="{searchTerms}"/>
+    <Param name="source" value="opensearch"/>
+  </Url>
+</OpenSearchDescription>"#;
+
+        let description = parse_description(xml).unwrap();
+
+        assert_eq!(
+            description.url,
+            "https://example.com/search?q={searchTerms}&source=opensearch"
+        );
+    }
+
+    #[test]
+    fn reads_suggestions_url_and_largest_image() {
+        let xml = r#"<OpenSearchDescription xmlns="http://a9.com/-/spec/opensearch/1.1/">
+  <ShortName>Example</ShortName>
+  <Url type="application/x-suggestions+json" template="https://example.com/suggest?q={searchTerms}"/>
+  <Url type="text/html" method="post" template="https://example.com/post"/>
+  <Url type="text/html" template="https://example.com/?q={searchTerms}"/>
+  <Image width="16" height="16">https://example.com/favicon.ico</Image>
+  <Image width="64" height="64">https://example.com/logo.png</Image>
+</OpenSearchDescription>"#;
+
+        let description = parse_description(xml).unwrap();
+
+        assert_eq!(description.url, "https://example.com/?q={searchTerms}");
+        assert_eq!(
+            description.suggest_url.as_deref(),
+            Some("https://example.com/suggest?q={searchTerms}")
+        );
+        assert_eq!(
+            description.image.as_deref(),
+            Some("https://example.com/logo.png")
+        );
+    }
+
+    #[test]
+    fn rejects_description_without_short_name() {
+        let xml = r#"<OpenSearchDescription>
+  <Url type="text/html" template="https://example.com/?q={searchTerms}"/>
+</OpenSearchDescription>"#;
+
+        assert!(matches!(
+            parse_description(xml),
+            Err(ImportError::Invalid(reason)) if reason == "no ShortName"
+        ));
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(matches!(
+            parse_description("<html><body/></html>"),
+            Err(ImportError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn url_extension_ignores_query_and_fragment() {
+        assert_eq!(url_extension("https://example.com/favicon.png?v=2"), "png");
+        assert_eq!(url_extension("https://example.com/logo.SVG#icon"), "svg");
+        assert_eq!(url_extension("https://example.com/favicon"), "");
+    }
+}
@@ -0,0 +1,323 @@
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("processes", LogLevel::Debug);
+}
+
+// cpu usage is measured between two reads of /proc
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// process of the current user
+#[derive(Debug, Clone)]
+pub struct Process {
+    pub pid: i32,
+    pub name: String,
+    pub command_line: String,
+    /// share of one cpu core, can go over 100 for multithreaded processes
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+impl Process {
+    /// names the process can be recognized by: its name and the program it runs
+    pub fn program_names(&self) -> Vec<&str> {
+        let program = self
+            .command_line
+            .split_whitespace()
+            .next()
+            .map(|program| program.rsplit('/').next().unwrap_or(program));
+        std::iter::once(self.name.as_str()).chain(program).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    /// ask the process to quit
+    Terminate,
+    /// stop the process right away
+    Kill,
+}
+
+impl Signal {
+    fn number(&self) -> i32 {
+        match self {
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+pub fn send_signal(pid: i32, signal: Signal) -> std::io::Result<()> {
+    // SAFETY: kill has no memory safety requirements
+    let result = unsafe { libc::kill(pid, signal.number()) };
+    if result == 0 {
+        LOG.debug(&format!("sent {:?} to {}", signal, pid));
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// cpu ticks of the processes and of the whole machine at one point in time
+struct Sample {
+    processes: HashMap<i32, u64>,
+    total: u64,
+}
+
+/// processes of the current user with their cpu usage over a short interval
+pub async fn list_processes() -> Vec<Process> {
+    let Ok(first) = tokio::task::spawn_blocking(read_sample).await else {
+        return Vec::new();
+    };
+    tokio::time::sleep(SAMPLE_INTERVAL).await;
+
+    tokio::task::spawn_blocking(move || {
+        let second = read_sample();
+        let total = second.total.saturating_sub(first.total).max(1) as f32;
+        let cpus = cpu_count() as f32;
+
+        second
+            .processes
+            .iter()
+            .filter_map(|(&pid, &ticks)| {
+                let before = first.processes.get(&pid).copied().unwrap_or(ticks);
+                let cpu_percent = ticks.saturating_sub(before) as f32 / total * cpus * 100.0;
+                read_process(pid, cpu_percent)
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+fn read_sample() -> Sample {
+    // SAFETY: getuid and getpid always succeed
+    let (uid, own_pid) = unsafe { (libc::getuid(), libc::getpid()) };
+    let mut processes = HashMap::new();
+
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|n| n.parse::<i32>().ok())
+            else {
+                continue;
+            };
+            let owned = entry.metadata().is_ok_and(|m| m.uid() == uid);
+            if pid == own_pid || !owned {
+                continue;
+            }
+            if let Some(ticks) = process_ticks(&entry.path()) {
+                processes.insert(pid, ticks);
+            }
+        }
+    }
+
+    Sample {
+        processes,
+        total: total_ticks(),
+    }
+}
+
+fn process_ticks(dir: &Path) -> Option<u64> {
+    parse_process_ticks(&std::fs::read_to_string(dir.join("stat")).ok()?)
+}
+
+/// user and system time of the process from the content of /proc/<pid>/stat
+fn parse_process_ticks(stat: &str) -> Option<u64> {
+    // the name in parentheses may contain spaces, the fields start after it
+    let fields = stat
+        .rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    let utime = fields.get(11)?.parse::<u64>().ok()?;
+    let stime = fields.get(12)?.parse::<u64>().ok()?;
+    Some(utime + stime)
+}
+
+fn total_ticks() -> u64 {
+    std::fs::read_to_string("/proc/stat")
+        .ok()
+        .and_then(|stat| parse_total_ticks(&stat))
+        .unwrap_or_default()
+}
+
+/// ticks of all cpus together from the first line of /proc/stat
+fn parse_total_ticks(stat: &str) -> Option<u64> {
+    let line = stat.lines().next()?.strip_prefix("cpu ")?;
+    Some(
+        line.split_whitespace()
+            .filter_map(|ticks| ticks.parse::<u64>().ok())
+            .sum(),
+    )
+}
+
+fn cpu_count() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn read_process(pid: i32, cpu_percent: f32) -> Option<Process> {
+    let dir = Path::new("/proc").join(pid.to_string());
+
+    let command_line = parse_command_line(&std::fs::read(dir.join("cmdline")).ok()?)?;
+
+    let name = std::fs::read_to_string(dir.join("comm"))
+        .ok()?
+        .trim()
+        .to_string();
+
+    // resident pages are the second field of statm
+    // SAFETY: sysconf has no memory safety requirements
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(0) as u64;
+    let memory_bytes = std::fs::read_to_string(dir.join("statm"))
+        .ok()
+        .and_then(|statm| statm.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .unwrap_or_default()
+        * page_size;
+
+    Some(Process {
+        pid,
+        name,
+        command_line,
+        cpu_percent,
+        memory_bytes,
+    })
+}
+
+/// NUL separated arguments of /proc/<pid>/cmdline joined by spaces
+fn parse_command_line(cmdline: &[u8]) -> Option<String> {
+    // kernel threads have no command line
+    if cmdline.is_empty() {
+        return None;
+    }
+    Some(
+        String::from_utf8_lossy(cmdline)
+            .split('\0')
+            .filter(|arg| !arg.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// score of the query as a subsequence of the text, higher is better
+///
+/// consecutive and word-start matches count more, so `ff` finds firefox
+/// and `code` ranks `code` above `vscode-helper`
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text = text.to_lowercase();
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut chars = text.char_indices();
+
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let (index, _) = chars.find(|(_, c)| *c == query_char)?;
+
+        score += 1;
+        if previous.is_some_and(|previous| index == previous + 1) {
+            score += 5;
+        }
+        let word_start = index == 0
+            || text[..index]
+                .chars()
+                .last()
+                .is_some_and(|c| !c.is_alphanumeric());
+        if word_start {
+            score += 3;
+        }
+        previous = Some(index);
+    }
+
+    // prefer shorter names for the same matches
+    Some(score * 100 - text.len() as i32)
+}
+
+/// memory size in binary units, e.g. `512.0 MiB`
+pub fn format_memory(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_ticks_after_the_process_name() {
+        // the name has spaces and a parenthesis of its own
+        let stat =
+            "4242 (Web Content (1)) S 1 4242 4242 0 -1 4194560 1000 0 0 0 120 30 0 0 20 0 30 0";
+        assert_eq!(parse_process_ticks(stat), Some(150));
+        assert_eq!(parse_process_ticks("4242 (short) S 1"), None);
+    }
+
+    #[test]
+    fn sums_the_cpu_line_of_proc_stat() {
+        let stat = "cpu  100 5 50 1000 10 0 2 0 0 0\ncpu0 50 2 25 500 5 0 1 0 0 0\n";
+        assert_eq!(parse_total_ticks(stat), Some(1167));
+        assert_eq!(parse_total_ticks("intr 1 2 3"), None);
+    }
+
+    #[test]
+    fn joins_command_line_arguments() {
+        assert_eq!(
+            parse_command_line(b"/usr/bin/python3\0-m\0http.server\0").as_deref(),
+            Some("/usr/bin/python3 -m http.server")
+        );
+        assert_eq!(parse_command_line(b""), None);
+    }
+
+    #[test]
+    fn knows_the_name_and_the_program() {
+        let process = Process {
+            pid: 1,
+            name: "MainThread".to_string(),
+            command_line: "/opt/app/bin/electron --type=renderer".to_string(),
+            cpu_percent: 0.0,
+            memory_bytes: 0,
+        };
+        assert_eq!(process.program_names(), vec!["MainThread", "electron"]);
+    }
+
+    #[test]
+    fn scores_subsequences() {
+        assert!(fuzzy_score("ff", "firefox").is_some());
+        assert_eq!(fuzzy_score("xz", "firefox"), None);
+        assert_eq!(fuzzy_score("", "firefox"), Some(-7));
+    }
+
+    #[test]
+    fn ranks_word_starts_and_runs_higher() {
+        let exact = fuzzy_score("code", "code").unwrap();
+        let helper = fuzzy_score("code", "vscode-helper").unwrap();
+        let scattered = fuzzy_score("code", "cargo-doc-server").unwrap();
+        assert!(exact > helper, "{} <= {}", exact, helper);
+        assert!(helper > scattered, "{} <= {}", helper, scattered);
+        assert_eq!(
+            fuzzy_score("FIRE fox", "firefox"),
+            fuzzy_score("firefox", "firefox")
+        );
+    }
+
+    #[test]
+    fn formats_memory_in_binary_units() {
+        assert_eq!(format_memory(512), "512 B");
+        assert_eq!(format_memory(1536), "1.5 KiB");
+        assert_eq!(format_memory(512 * 1024 * 1024), "512.0 MiB");
+    }
+}
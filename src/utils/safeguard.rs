@@ -0,0 +1,216 @@
+use crate::utils::{
+    config::CONFIG,
+    logger::{LogLevel, Logger},
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("safeguard", LogLevel::Debug);
+}
+
+/// patterns of commands that destroy data or take the machine down, with the reason
+const BUILT_IN_RULES: &[(&str, &str)] = &[
+    // recursive deletes of the root, home, the working directory or system directories,
+    // `rm -rf ./build` is everyday work
+    (
+        r"\brm\s+(-\S+\s+)*-([a-zA-Z]*[rR][a-zA-Z]*|-recursive)\s+(\S+\s+)*(/\*?|~/?\*?|\$\{?HOME\}?/?\*?|\*|\.\.?/?|/(bin|boot|dev|etc|home|lib|lib64|opt|root|sbin|srv|usr|var)/?\*?)(\s|[;&|]|$)",
+        "Deletes files recursively",
+    ),
+    (r"\brm\b.*--no-preserve-root", "Deletes files recursively"),
+    (r"\bdd\b.*\bof=/dev/", "Overwrites a device"),
+    (r">\s*/dev/(sd|hd|vd|nvme|mmcblk)", "Overwrites a disk"),
+    (r"\bmkfs(\.\w+)?\b", "Formats a file system"),
+    (
+        r"\b(wipefs|shred|fdisk|sfdisk|cfdisk|gdisk|parted)\b",
+        "Changes or wipes partitions",
+    ),
+    (
+        r"\bch(mod|own|grp)\s+(-\S+\s+)*-[a-zA-Z]*R",
+        "Changes permissions recursively",
+    ),
+    (r":\(\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:", "Fork bomb"),
+    (
+        r"\b(shutdown|reboot|poweroff|halt)\b",
+        "Turns off or restarts the machine",
+    ),
+    (
+        r"\|\s*(sudo\s+)?(ba|z|da|fi)?sh\b",
+        "Runs downloaded or piped code in a shell",
+    ),
+    (
+        r"\bgit\s+(reset\s+--hard|clean\s+-\S*f|push\s+.*(-f\b|--force))",
+        "Throws away git changes",
+    ),
+];
+
+static SAFEGUARD: Lazy<Safeguard> = Lazy::new(Safeguard::from_config);
+
+struct DangerRule {
+    pattern: Regex,
+    reason: String,
+}
+
+/// rules for commands that need a confirmation before they run
+pub struct Safeguard {
+    rules: Vec<DangerRule>,
+}
+
+impl Safeguard {
+    fn from_config() -> Self {
+        Self::new(CONFIG.run.confirm_dangerous, &CONFIG.run.dangerous_patterns)
+    }
+
+    /// built-in rules followed by the user's patterns, or none when disabled
+    fn new(enabled: bool, patterns: &[String]) -> Self {
+        if !enabled {
+            return Self { rules: Vec::new() };
+        }
+
+        let built_in = BUILT_IN_RULES
+            .iter()
+            .map(|(pattern, reason)| (pattern.to_string(), reason.to_string()));
+        let custom = patterns.iter().map(|pattern| {
+            (
+                pattern.clone(),
+                "Matches one of your dangerous patterns".to_string(),
+            )
+        });
+
+        let rules = built_in
+            .chain(custom)
+            .filter_map(|(pattern, reason)| match Regex::new(&pattern) {
+                Ok(pattern) => Some(DangerRule { pattern, reason }),
+                Err(e) => {
+                    LOG.error(&format!("Invalid dangerous pattern '{}': {}", pattern, e));
+                    None
+                }
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    fn check(&self, command: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.pattern.is_match(command))
+            .map(|rule| rule.reason.as_str())
+    }
+}
+
+/// why the command needs a confirmation, if it does
+pub fn check_command(command: &str) -> Option<&'static str> {
+    SAFEGUARD.check(command)
+}
+
+/// argv as it would be typed into a shell, to show what exactly gets run
+pub fn format_argv(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn safeguard() -> Safeguard {
+        Safeguard::new(true, &[])
+    }
+
+    #[test]
+    fn catches_destructive_commands() {
+        let safeguard = safeguard();
+        for command in [
+            "rm -rf ~",
+            "rm -rf /",
+            "rm -rf /*",
+            "sudo rm -r -f ~/",
+            "rm --recursive --force $HOME",
+            "rm -rf .",
+            "rm -rf /usr",
+            "rm --no-preserve-root -r /",
+            "dd if=image.iso of=/dev/sda bs=4M",
+            "cat zero > /dev/nvme0n1",
+            "mkfs.ext4 /dev/sdb1",
+            "mkfs -t vfat /dev/sdc",
+            ":(){ :|:& };:",
+            "curl -fsSL https://example.com/install | sh",
+            "git push --force origin main",
+        ] {
+            assert!(safeguard.check(command).is_some(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn lets_near_misses_through() {
+        let safeguard = safeguard();
+        for command in [
+            "rm -rf ./build",
+            "rm -rf target node_modules",
+            "rm notes.txt",
+            "echo dd",
+            "dd if=/dev/zero of=disk.img bs=1M count=10",
+            "ls /dev/sda",
+            "git push origin main",
+            "echo ~",
+        ] {
+            assert_eq!(safeguard.check(command), None, "{}", command);
+        }
+    }
+
+    #[test]
+    fn names_the_reason() {
+        let safeguard = safeguard();
+        assert_eq!(
+            safeguard.check("rm -rf ~"),
+            Some("Deletes files recursively")
+        );
+        assert_eq!(safeguard.check(":(){ :|:& };:"), Some("Fork bomb"));
+    }
+
+    #[test]
+    fn adds_user_patterns_after_the_built_in_ones() {
+        let patterns = [
+            r"\bkubectl\s+delete\b".to_string(),
+            // invalid patterns are skipped, not fatal
+            "(unclosed".to_string(),
+        ];
+        let safeguard = Safeguard::new(true, &patterns);
+
+        assert_eq!(
+            safeguard.check("kubectl delete pod web"),
+            Some("Matches one of your dangerous patterns")
+        );
+        assert_eq!(safeguard.check("kubectl get pods"), None);
+        assert_eq!(safeguard.rules.len(), BUILT_IN_RULES.len() + 1);
+    }
+
+    #[test]
+    fn checks_nothing_when_disabled() {
+        let safeguard = Safeguard::new(false, &["rm".to_string()]);
+        assert_eq!(safeguard.check("rm -rf /"), None);
+    }
+
+    #[test]
+    fn quotes_arguments_like_a_shell() {
+        let argv = ["zsh", "-c", "rm -rf ~", "it's", ""].map(String::from);
+        assert_eq!(format_argv(&argv), r"zsh -c 'rm -rf ~' 'it'\''s' ''");
+        assert_eq!(
+            format_argv(&["ls".to_string(), "-la".to_string()]),
+            "ls -la"
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::utils::{
+    command::is_executable,
+    config::Config,
+    logger::{LogLevel, Logger},
+};
+use std::path::{Path, PathBuf};
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("scripts", LogLevel::Debug);
+}
+
+// metadata is only read from the comments at the top of the script
+const MAX_HEADER_LINES: usize = 30;
+
+/// executable dropped into the scripts directory
+///
+/// metadata comes from `@key: value` comments at the top of the file:
+///
+/// ```sh
+/// #!/bin/sh
+/// # @name: Deploy staging
+/// # @description: Build and push to the staging server
+/// # @icon: network-server
+/// # @keywords: deploy, release
+/// # @terminal: true
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    pub path: PathBuf,
+    pub name: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+    pub keywords: Vec<String>,
+    /// run inside a terminal emulator
+    pub terminal: bool,
+    /// show the output in the result list
+    pub capture: bool,
+}
+
+impl Script {
+    fn matches(&self, query: &str) -> bool {
+        self.name.to_lowercase().contains(query)
+            || self
+                .description
+                .as_ref()
+                .is_some_and(|description| description.to_lowercase().contains(query))
+            || self
+                .keywords
+                .iter()
+                .any(|keyword| keyword.to_lowercase().contains(query))
+    }
+}
+
+pub fn scripts_dir() -> PathBuf {
+    Config::dir().join("scripts")
+}
+
+/// read all executable scripts of the directory, sorted by name
+pub fn load_scripts(dir: &Path) -> Vec<Script> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut scripts = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_executable(path))
+        .map(|path| parse_script(&path))
+        .collect::<Vec<_>>();
+    scripts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    LOG.debug(&format!(
+        "loaded {} scripts from {}",
+        scripts.len(),
+        dir.display()
+    ));
+    scripts
+}
+
+/// scripts whose name, description or keywords contain the query
+pub fn search_scripts<'a>(scripts: &'a [Script], query: &str) -> Vec<&'a Script> {
+    let query = query.to_lowercase();
+    scripts
+        .iter()
+        .filter(|script| script.matches(&query))
+        .collect()
+}
+
+fn parse_script(path: &Path) -> Script {
+    let mut script = Script {
+        path: path.to_path_buf(),
+        name: path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        ..Default::default()
+    };
+
+    // binaries or unreadable files keep the defaults
+    if let Ok(content) = std::fs::read_to_string(path) {
+        parse_header(&mut script, &content);
+    }
+    script
+}
+
+/// fill in the fields from the `@key: value` comments at the top of the content
+fn parse_header(script: &mut Script, content: &str) {
+    for line in content.lines().take(MAX_HEADER_LINES) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#!") {
+            continue;
+        }
+        let Some(comment) = line
+            .strip_prefix('#')
+            .or_else(|| line.strip_prefix("//"))
+            .or_else(|| line.strip_prefix("--"))
+        else {
+            // the header ends with the first line of code
+            break;
+        };
+
+        let Some((key, value)) = comment
+            .trim()
+            .strip_prefix('@')
+            .and_then(|field| field.split_once(':'))
+        else {
+            continue;
+        };
+        let value = value.trim();
+
+        match key.trim() {
+            "name" => script.name = value.to_string(),
+            "description" => script.description = Some(value.to_string()),
+            "icon" => script.icon = Some(value.to_string()),
+            "keywords" => {
+                script.keywords = value
+                    .split(',')
+                    .map(|keyword| keyword.trim().to_string())
+                    .filter(|keyword| !keyword.is_empty())
+                    .collect()
+            }
+            "terminal" => script.terminal = value == "true",
+            "capture" => script.capture = value == "true",
+            other => LOG.warn(&format!(
+                "unknown field '{}' in {}",
+                other,
+                script.path.display()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(content: &str) -> Script {
+        let mut script = Script {
+            path: PathBuf::from("/scripts/deploy.sh"),
+            name: "deploy".to_string(),
+            ..Default::default()
+        };
+        parse_header(&mut script, content);
+        script
+    }
+
+    #[test]
+    fn reads_all_fields() {
+        let script = header(
+            "#!/bin/sh\n\
+             # @name: Deploy staging\n\
+             # @description: Build and push to the staging server\n\
+             # @icon: network-server\n\
+             # @keywords: deploy, release, ,staging\n\
+             # @terminal: true\n\
+             # @capture: true\n\
+             make deploy\n",
+        );
+        assert_eq!(script.name, "Deploy staging");
+        assert_eq!(
+            script.description.as_deref(),
+            Some("Build and push to the staging server")
+        );
+        assert_eq!(script.icon.as_deref(), Some("network-server"));
+        assert_eq!(script.keywords, vec!["deploy", "release", "staging"]);
+        assert!(script.terminal);
+        assert!(script.capture);
+    }
+
+    #[test]
+    fn keeps_defaults_without_header() {
+        let script = header("#!/bin/sh\necho hello\n");
+        assert_eq!(script.name, "deploy");
+        assert_eq!(script.description, None);
+        assert!(script.keywords.is_empty());
+        assert!(!script.terminal);
+        assert!(!script.capture);
+    }
+
+    #[test]
+    fn accepts_other_comment_styles() {
+        let script = header("// @name: From JavaScript\n");
+        assert_eq!(script.name, "From JavaScript");
+        let script = header("-- @description: From Lua\n");
+        assert_eq!(script.description.as_deref(), Some("From Lua"));
+    }
+
+    #[test]
+    fn stops_at_first_line_of_code() {
+        let script = header("#!/bin/sh\n\n# @name: Header\nset -e\n# @icon: ignored\n");
+        assert_eq!(script.name, "Header");
+        assert_eq!(script.icon, None);
+    }
+
+    #[test]
+    fn ignores_plain_comments_and_other_values() {
+        let script = header("# deploys the app\n# @terminal: yes\n# @colour: red\n");
+        assert_eq!(script.name, "deploy");
+        assert!(!script.terminal);
+    }
+
+    #[test]
+    fn only_reads_the_first_lines() {
+        let mut content = "#\n".repeat(MAX_HEADER_LINES);
+        content.push_str("# @name: Too late\n");
+        assert_eq!(header(&content).name, "deploy");
+    }
+
+    #[test]
+    fn searches_name_description_and_keywords() {
+        let scripts = vec![
+            header("# @name: Deploy staging\n"),
+            header("# @name: Backup\n# @description: Copy HOME to the NAS\n"),
+            header("# @name: Cleanup\n# @keywords: tmp, Cache\n"),
+        ];
+        let names = |query: &str| {
+            search_scripts(&scripts, query)
+                .iter()
+                .map(|script| script.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("STAGING"), vec!["Deploy staging"]);
+        assert_eq!(names("nas"), vec!["Backup"]);
+        assert_eq!(names("cache"), vec!["Cleanup"]);
+        assert!(names("missing").is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// makes sure only the latest query gets to show its results
+///
+/// every keystroke starts a new generation, which cancels all searches
+/// started before it, no matter in which order they finish
+#[derive(Clone)]
+pub struct SearchCoordinator {
+    generation: Arc<AtomicU64>,
+    cancelled: Arc<Notify>,
+    debounce: Duration,
+}
+
+/// handle of a single search, valid until a newer one starts
+#[derive(Clone)]
+pub struct SearchToken {
+    generation: u64,
+    current: Arc<AtomicU64>,
+    cancelled: Arc<Notify>,
+}
+
+impl SearchToken {
+    pub fn is_current(&self) -> bool {
+        self.current.load(Ordering::SeqCst) == self.generation
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        !self.is_current()
+    }
+
+    /// resolves once a newer search has started
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.cancelled.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl SearchCoordinator {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            generation: Arc::new(AtomicU64::new(0)),
+            cancelled: Arc::new(Notify::new()),
+            debounce,
+        }
+    }
+
+    /// start a new search, cancelling every earlier one
+    pub fn begin(&self) -> SearchToken {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.cancelled.notify_waiters();
+
+        SearchToken {
+            generation,
+            current: self.generation.clone(),
+            cancelled: self.cancelled.clone(),
+        }
+    }
+
+    /// cancel the running search without starting a new one
+    pub fn cancel(&self) {
+        self.begin();
+    }
+
+    /// wait for the debounce delay, then run the search
+    ///
+    /// returns `None` when a newer search started before this one finished
+    pub async fn run<T>(&self, token: &SearchToken, search: impl Future<Output = T>) -> Option<T> {
+        if !self.debounce.is_zero() {
+            tokio::select! {
+                _ = tokio::time::sleep(self.debounce) => {}
+                _ = token.cancelled() => return None,
+            }
+        }
+
+        if token.is_cancelled() {
+            return None;
+        }
+
+        let result = tokio::select! {
+            result = search => result,
+            _ = token.cancelled() => return None,
+        };
+
+        token.is_current().then_some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn slow_earlier_search_does_not_override_latest() {
+        let coordinator = SearchCoordinator::new(Duration::ZERO);
+
+        let first = coordinator.begin();
+        let slow = {
+            let coordinator = coordinator.clone();
+            tokio::spawn(async move {
+                coordinator
+                    .run(&first, async {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        "first"
+                    })
+                    .await
+            })
+        };
+
+        let second = coordinator.begin();
+        let fast = coordinator.run(&second, async { "second" }).await;
+
+        assert_eq!(fast, Some("second"));
+        assert_eq!(slow.await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn debounce_drops_superseded_queries() {
+        let coordinator = SearchCoordinator::new(Duration::from_millis(30));
+        let ran = Arc::new(AtomicU64::new(0));
+
+        let mut handles = Vec::new();
+        for query in ["f", "fi", "fir"] {
+            let token = coordinator.begin();
+            let coordinator = coordinator.clone();
+            let ran = ran.clone();
+            handles.push(tokio::spawn(async move {
+                coordinator
+                    .run(&token, async move {
+                        ran.fetch_add(1, Ordering::SeqCst);
+                        query
+                    })
+                    .await
+            }));
+        }
+
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap());
+        }
+
+        assert_eq!(results, vec![None, None, Some("fir")]);
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_running_search() {
+        let coordinator = SearchCoordinator::new(Duration::ZERO);
+        let token = coordinator.begin();
+
+        let running = {
+            let coordinator = coordinator.clone();
+            let token = token.clone();
+            tokio::spawn(async move { coordinator.run(&token, std::future::pending::<()>()).await })
+        };
+
+        tokio::task::yield_now().await;
+        coordinator.cancel();
+
+        assert!(token.is_cancelled());
+        assert_eq!(running.await.unwrap(), None);
+    }
+}
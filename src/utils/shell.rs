@@ -0,0 +1,310 @@
+use crate::utils::{
+    aliases,
+    config::CONFIG,
+    logger::{LogLevel, Logger},
+};
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("shell", LogLevel::Debug);
+}
+
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(5);
+// separates whatever the shell config prints from the environment itself
+const ENV_MARKER: &str = "__STARLIGHT_ENV__";
+
+/// variables of the running session, a cached login shell only has stale ones
+const SESSION_VARS: &[&str] = &[
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "SSH_AUTH_SOCK",
+    "SSH_AGENT_PID",
+    "GPG_AGENT_INFO",
+    "XDG_RUNTIME_DIR",
+    "XDG_ACTIVATION_TOKEN",
+    "DESKTOP_STARTUP_ID",
+    "WINDOWID",
+    "SWAYSOCK",
+    "I3SOCK",
+    "HYPRLAND_INSTANCE_SIGNATURE",
+    "NIRI_SOCKET",
+];
+const SESSION_PREFIXES: &[&str] = &["XDG_SESSION_", "XDG_SEAT", "XDG_VTNR"];
+
+/// variables of the login shell missing from our environment, and list
+/// variables like PATH with the entries the login shell adds, imported once
+static LOGIN_ENV: OnceCell<HashMap<String, String>> = OnceCell::new();
+
+/// shell used to run commands, `$SHELL` unless configured
+pub fn shell_program() -> String {
+    CONFIG
+        .run
+        .shell
+        .clone()
+        .or_else(|| std::env::var("SHELL").ok())
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| "sh".to_string())
+}
+
+/// argv running the command through the configured shell
+pub fn shell_argv(command: &str) -> Vec<String> {
+    let mut argv = vec![shell_program()];
+    if CONFIG.run.login_shell {
+        argv.push("-l".to_string());
+    }
+    // aliases and functions only exist in interactive shells
+    let program = command.split_whitespace().next().unwrap_or_default();
+    if CONFIG.run.interactive_shell || aliases::is_defined(program) {
+        argv.push("-i".to_string());
+    }
+    argv.extend(["-c".to_string(), command.to_string()]);
+    argv
+}
+
+pub fn shell_command(command: &str) -> std::process::Command {
+    let argv = shell_argv(command);
+    let mut cmd = std::process::Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    if let Some(env) = login_env() {
+        cmd.envs(env);
+    }
+    cmd
+}
+
+pub fn async_shell_command(command: &str) -> tokio::process::Command {
+    let argv = shell_argv(command);
+    let mut cmd = tokio::process::Command::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    if let Some(env) = login_env() {
+        cmd.envs(env);
+    }
+    cmd
+}
+
+pub fn login_env() -> Option<&'static HashMap<String, String>> {
+    LOGIN_ENV.get()
+}
+
+/// PATH of the login shell when imported, otherwise our own
+pub fn path_var() -> String {
+    login_env()
+        .and_then(|env| env.get("PATH").cloned())
+        .unwrap_or_else(|| std::env::var("PATH").unwrap_or_default())
+}
+
+fn env_cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_default()
+        .join("starlight")
+        .join("login-env")
+}
+
+/// import the login shell environment
+///
+/// a cached copy from the last start is used right away while a fresh one is
+/// imported in the background for the next start, since login shells can be slow
+pub async fn import_login_env() -> Option<&'static HashMap<String, String>> {
+    if !CONFIG.run.import_login_env {
+        return None;
+    }
+    if let Some(env) = login_env() {
+        return Some(env);
+    }
+
+    let current = std::env::vars().collect::<HashMap<_, _>>();
+    if let Some(cached) = read_cached_env() {
+        tokio::spawn(async {
+            if let Some(env) = read_login_env().await {
+                write_cached_env(&env);
+            }
+        });
+        return Some(LOGIN_ENV.get_or_init(|| layer_env(cached, &current)));
+    }
+
+    let env = read_login_env().await?;
+    write_cached_env(&env);
+    Some(LOGIN_ENV.get_or_init(|| layer_env(env, &current)))
+}
+
+/// the login values under the current environment
+///
+/// variables we already have win, only lists like PATH get the entries of the
+/// login shell appended, so `.zprofile` additions show up without replacing ours
+fn layer_env(
+    login: HashMap<String, String>,
+    current: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    login
+        .into_iter()
+        .filter_map(|(key, value)| match current.get(&key) {
+            None => Some((key, value)),
+            Some(ours) if is_path_list(&key) => {
+                let mut entries = ours.split(':').collect::<Vec<_>>();
+                for entry in value.split(':') {
+                    if !entry.is_empty() && !entries.contains(&entry) {
+                        entries.push(entry);
+                    }
+                }
+                let merged = entries.join(":");
+                (&merged != ours).then_some((key, merged))
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+fn is_path_list(key: &str) -> bool {
+    key.ends_with("PATH") || matches!(key, "XDG_DATA_DIRS" | "XDG_CONFIG_DIRS")
+}
+
+fn is_session_var(key: &str) -> bool {
+    SESSION_VARS.contains(&key)
+        || SESSION_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+}
+
+async fn read_login_env() -> Option<HashMap<String, String>> {
+    let shell = shell_program();
+    let script = format!("printf '\\0{}\\0'; env -0", ENV_MARKER);
+
+    let mut cmd = tokio::process::Command::new(&shell);
+    cmd.arg("-l");
+    if CONFIG.run.interactive_shell {
+        cmd.arg("-i");
+    }
+    cmd.arg("-c")
+        .arg(script)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+
+    let output = match tokio::time::timeout(IMPORT_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            LOG.error(&format!("Failed to run login shell {}: {}", shell, e));
+            return None;
+        }
+        Err(_) => {
+            LOG.warn(&format!("login shell {} timed out", shell));
+            return None;
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let marker = format!("\0{}\0", ENV_MARKER);
+    let (_, env) = stdout.split_once(&marker)?;
+    let env = parse_env(env);
+    LOG.debug(&format!(
+        "imported {} variables from {} login shell",
+        env.len(),
+        shell
+    ));
+    Some(env)
+}
+
+/// parse NUL separated `KEY=value` pairs as printed by `env -0`
+fn parse_env(content: &str) -> HashMap<String, String> {
+    content
+        .split('\0')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(key, _)| !key.is_empty() && !key.contains('\n'))
+        // these belong to the shell that printed them, not to us
+        .filter(|(key, _)| !matches!(*key, "PWD" | "OLDPWD" | "SHLVL" | "_"))
+        .filter(|(key, _)| !is_session_var(key))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn read_cached_env() -> Option<HashMap<String, String>> {
+    let content = std::fs::read_to_string(env_cache_path()).ok()?;
+    let env = parse_env(&content);
+    (!env.is_empty()).then_some(env)
+}
+
+/// cache what the login shell sets differently from the environment we got
+fn write_cached_env(env: &HashMap<String, String>) {
+    let path = env_cache_path();
+    let content = env
+        .iter()
+        .filter(|(key, value)| std::env::var(key).ok().as_ref() != Some(*value))
+        .map(|(key, value)| format!("{}={}\0", key, value))
+        .collect::<String>();
+
+    if let Err(e) = write_private_file(&path, &content) {
+        LOG.error(&format!(
+            "Failed to cache login environment to {}: {}",
+            path.display(),
+            e
+        ));
+    }
+}
+
+/// write a file only the user can read, for things that may hold tokens
+pub fn write_private_file(path: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_env_without_session_variables() {
+        let parsed = parse_env(
+            "PATH=/usr/bin\0WAYLAND_DISPLAY=wayland-1\0XDG_SESSION_ID=3\0PWD=/home\0EDITOR=vi=m\0",
+        );
+
+        assert_eq!(parsed, env(&[("PATH", "/usr/bin"), ("EDITOR", "vi=m")]));
+    }
+
+    #[test]
+    fn current_environment_wins() {
+        let login = env(&[("EDITOR", "nano"), ("GOPATH", "/go"), ("LANG", "C")]);
+        let current = env(&[("EDITOR", "vim"), ("LANG", "C")]);
+
+        let layered = layer_env(login, &current);
+
+        assert_eq!(layered, env(&[("GOPATH", "/go")]));
+    }
+
+    #[test]
+    fn path_lists_get_the_login_entries_appended() {
+        let login = env(&[
+            ("PATH", "/home/me/.nix-profile/bin:/usr/bin"),
+            ("MANPATH", "/usr/share/man"),
+        ]);
+        let current = env(&[("PATH", "/usr/bin:/bin"), ("MANPATH", "/usr/share/man")]);
+
+        let layered = layer_env(login, &current);
+
+        assert_eq!(
+            layered,
+            env(&[("PATH", "/usr/bin:/bin:/home/me/.nix-profile/bin")])
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::utils::logger::{LogLevel, Logger};
+use once_cell::sync::Lazy;
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("suggestions", LogLevel::Debug);
+}
+
+// shared, so connections to the engines are reused between keystrokes
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .user_agent(concat!("starlight/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_default()
+});
+
+/// query completions from an OpenSearch suggestions endpoint
+///
+/// failures (offline, timeout, unexpected response) only get logged and
+/// give no suggestions, dropping the future cancels the request
+pub async fn fetch_suggestions(url: &str, timeout: Duration) -> Vec<String> {
+    let response = CLIENT
+        .get(url)
+        .timeout(timeout)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    let body = match response {
+        Ok(response) => response.bytes().await,
+        Err(e) => Err(e),
+    };
+
+    match body {
+        Ok(body) => parse_suggestions(&String::from_utf8_lossy(&body)).unwrap_or_else(|| {
+            LOG.warn(&format!("unexpected suggestions response from {}", url));
+            Vec::new()
+        }),
+        Err(e) => {
+            LOG.debug(&format!("no suggestions from {}: {}", url, e));
+            Vec::new()
+        }
+    }
+}
+
+/// suggestions of an OpenSearch response, `["query", ["first", "second"], ...]`
+pub fn parse_suggestions(body: &str) -> Option<Vec<String>> {
+    let value = serde_json::from_str::<serde_json::Value>(body).ok()?;
+    let suggestions = value.as_array()?.get(1)?.as_array()?;
+    Some(
+        suggestions
+            .iter()
+            .filter_map(|suggestion| suggestion.as_str())
+            .map(|suggestion| suggestion.trim().to_string())
+            .filter(|suggestion| !suggestion.is_empty())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// serve a single request with the body, returning the url and the request line
+    async fn serve_once(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/complete?q=", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let read = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..read]).to_string();
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            request.lines().next().unwrap_or_default().to_string()
+        });
+
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn fetches_suggestions_from_endpoint() {
+        let (url, server) = serve_once(r#"["rust", ["rust lang", "rustup", " "]]"#).await;
+
+        let suggestions = fetch_suggestions(&format!("{}rust", url), Duration::from_secs(5)).await;
+
+        assert_eq!(suggestions, vec!["rust lang", "rustup"]);
+        assert!(server.await.unwrap().starts_with("GET /complete?q=rust "));
+    }
+
+    #[tokio::test]
+    async fn invalid_response_gives_no_suggestions() {
+        let (url, _server) = serve_once("<html>rate limited</html>").await;
+
+        let suggestions = fetch_suggestions(&url, Duration::from_secs(5)).await;
+
+        assert!(suggestions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn slow_endpoint_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        // accept the connection but never answer
+        let _server = tokio::spawn(async move {
+            let connection = listener.accept().await;
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            drop(connection);
+        });
+
+        let started = std::time::Instant::now();
+        let suggestions = fetch_suggestions(&url, Duration::from_millis(100)).await;
+
+        assert!(suggestions.is_empty());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn unreachable_endpoint_gives_no_suggestions() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+
+        let suggestions = fetch_suggestions(&url, Duration::from_secs(5)).await;
+
+        assert!(suggestions.is_empty());
+    }
+
+    #[test]
+    fn parses_opensearch_response() {
+        let body = r#"["lofi",["lofi hip hop","lofi girl"],[],{"google:suggesttype":[]}]"#;
+        assert_eq!(
+            parse_suggestions(body),
+            Some(vec!["lofi hip hop".to_string(), "lofi girl".to_string()])
+        );
+        assert_eq!(parse_suggestions(r#"{"error": "nope"}"#), None);
+    }
+}
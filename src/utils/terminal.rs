@@ -0,0 +1,132 @@
+use crate::utils::{
+    config::CONFIG,
+    logger::{LogLevel, Logger},
+    shell::{login_env, shell_argv},
+};
+
+lazy_static::lazy_static! {
+    static ref LOG: Logger = Logger::new("terminal", LogLevel::Debug);
+}
+
+/// terminals tried in order when none is configured
+const KNOWN_TERMINALS: &[&str] = &[
+    "kitty",
+    "alacritty",
+    "foot",
+    "wezterm",
+    "ghostty",
+    "gnome-terminal",
+    "konsole",
+    "xfce4-terminal",
+    "xterm",
+];
+
+// keeps the terminal open until Enter is pressed, showing the exit status
+//
+// run by `sh` around the user's shell, which might not speak POSIX sh like fish
+const HOLD_SCRIPT: &str = "\"$@\"; status=$?; printf '\\n[exited with status %s, press Enter to close]' \"$status\"; read _";
+
+/// terminal emulator used to run commands that need one
+#[derive(Debug, Clone)]
+pub struct Terminal {
+    /// terminal command with its own options, e.g. `kitty --single-instance`
+    command: Vec<String>,
+}
+
+impl Terminal {
+    /// configured terminal, then `$TERMINAL`, then the first known one installed
+    pub fn detect() -> Option<Self> {
+        let configured = CONFIG
+            .run
+            .terminal
+            .clone()
+            .or_else(|| std::env::var("TERMINAL").ok())
+            .filter(|terminal| !terminal.trim().is_empty());
+
+        let command = match configured {
+            Some(terminal) => terminal.split_whitespace().map(String::from).collect(),
+            None => {
+                let terminal = KNOWN_TERMINALS
+                    .iter()
+                    .find(|terminal| which::which(terminal).is_ok())?;
+                vec![terminal.to_string()]
+            }
+        };
+
+        Some(Self { command })
+    }
+
+    fn program_name(&self) -> &str {
+        self.command
+            .first()
+            .map(|program| program.rsplit('/').next().unwrap_or(program))
+            .unwrap_or_default()
+    }
+
+    /// arguments telling the terminal to run the rest of the command line
+    fn exec_args(&self) -> &'static [&'static str] {
+        match self.program_name() {
+            "kitty" | "foot" => &[],
+            "wezterm" => &["start", "--"],
+            "gnome-terminal" => &["--"],
+            // `-e` takes the command as one string there
+            "xfce4-terminal" => &["-x"],
+            _ => &["-e"],
+        }
+    }
+
+    /// full argv running the shell command inside the terminal
+    pub fn wrap(&self, command: &str, hold: bool) -> Vec<String> {
+        let mut argv = self.command.clone();
+        argv.extend(self.exec_args().iter().map(|arg| arg.to_string()));
+        if hold {
+            argv.extend(["sh", "-c", HOLD_SCRIPT, "sh"].map(String::from));
+        }
+        argv.extend(shell_argv(command));
+        argv
+    }
+}
+
+/// whether the command should always get a terminal, like htop or nvim
+pub fn needs_terminal(command: &str) -> bool {
+    let Some(program) = command.split_whitespace().next() else {
+        return false;
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+
+    CONFIG
+        .run
+        .terminal_programs
+        .iter()
+        .any(|name| name == program)
+}
+
+/// run a shell command inside the terminal emulator
+pub fn run_in_terminal(command: &str, hold: bool) -> Result<(), std::io::Error> {
+    let Some(terminal) = Terminal::detect() else {
+        LOG.error("No terminal emulator found, set `terminal` in the config");
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no terminal emulator found",
+        ));
+    };
+
+    let argv = terminal.wrap(command, hold);
+    LOG.debug(&format!("running in terminal: {:?}", argv));
+
+    let mut cmd = std::process::Command::new(&argv[0]);
+    if let Some(env) = login_env() {
+        cmd.envs(env);
+    }
+    cmd.args(&argv[1..])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+
+    let mut child = cmd.spawn()?;
+    // reap the terminal once it closes
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
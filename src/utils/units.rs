@@ -0,0 +1,798 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use uom::{Conversion, ConversionFactor};
+
+/// powers of the base quantities, units only convert within one dimension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dimension {
+    length: i8,
+    mass: i8,
+    time: i8,
+    temperature: i8,
+    data: i8,
+    angle: i8,
+}
+
+impl Dimension {
+    pub const NONE: Self = Self::new(0, 0, 0, 0, 0, 0);
+    pub const LENGTH: Self = Self::new(1, 0, 0, 0, 0, 0);
+    pub const AREA: Self = Self::new(2, 0, 0, 0, 0, 0);
+    pub const VOLUME: Self = Self::new(3, 0, 0, 0, 0, 0);
+    pub const MASS: Self = Self::new(0, 1, 0, 0, 0, 0);
+    pub const TIME: Self = Self::new(0, 0, 1, 0, 0, 0);
+    pub const FREQUENCY: Self = Self::new(0, 0, -1, 0, 0, 0);
+    pub const SPEED: Self = Self::new(1, 0, -1, 0, 0, 0);
+    pub const ENERGY: Self = Self::new(2, 1, -2, 0, 0, 0);
+    pub const POWER: Self = Self::new(2, 1, -3, 0, 0, 0);
+    pub const PRESSURE: Self = Self::new(-1, 1, -2, 0, 0, 0);
+    pub const TEMPERATURE: Self = Self::new(0, 0, 0, 1, 0, 0);
+    pub const DATA: Self = Self::new(0, 0, 0, 0, 1, 0);
+    pub const DATA_RATE: Self = Self::new(0, 0, -1, 0, 1, 0);
+    pub const ANGLE: Self = Self::new(0, 0, 0, 0, 0, 1);
+
+    const fn new(length: i8, mass: i8, time: i8, temperature: i8, data: i8, angle: i8) -> Self {
+        Self {
+            length,
+            mass,
+            time,
+            temperature,
+            data,
+            angle,
+        }
+    }
+
+    /// dimension of the quantity raised to the power, an area for a squared length
+    pub fn powi(self, power: i8) -> Self {
+        Self::new(
+            self.length.saturating_mul(power),
+            self.mass.saturating_mul(power),
+            self.time.saturating_mul(power),
+            self.temperature.saturating_mul(power),
+            self.data.saturating_mul(power),
+            self.angle.saturating_mul(power),
+        )
+    }
+}
+
+impl std::ops::Mul for Dimension {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.length.saturating_add(other.length),
+            self.mass.saturating_add(other.mass),
+            self.time.saturating_add(other.time),
+            self.temperature.saturating_add(other.temperature),
+            self.data.saturating_add(other.data),
+            self.angle.saturating_add(other.angle),
+        )
+    }
+}
+
+impl std::ops::Div for Dimension {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        self * other.powi(-1)
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match *self {
+            Self::LENGTH => "length",
+            Self::AREA => "area",
+            Self::VOLUME => "volume",
+            Self::MASS => "mass",
+            Self::TIME => "duration",
+            Self::FREQUENCY => "frequency",
+            Self::SPEED => "speed",
+            Self::ENERGY => "energy",
+            Self::POWER => "power",
+            Self::PRESSURE => "pressure",
+            Self::TEMPERATURE => "temperature",
+            Self::DATA => "data size",
+            Self::DATA_RATE => "data rate",
+            Self::ANGLE => "angle",
+            _ => {
+                // no name, so the powers of the base units, e.g. `m^2·s^-1`
+                let powers = [
+                    ("m", self.length),
+                    ("kg", self.mass),
+                    ("s", self.time),
+                    ("K", self.temperature),
+                    ("B", self.data),
+                    ("rad", self.angle),
+                ];
+                let parts = powers
+                    .iter()
+                    .filter(|(_, power)| *power != 0)
+                    .map(|(symbol, power)| match power {
+                        1 => symbol.to_string(),
+                        _ => format!("{}^{}", symbol, power),
+                    })
+                    .collect::<Vec<_>>();
+                if parts.is_empty() {
+                    return write!(f, "number");
+                }
+                return write!(f, "{}", parts.join("·"));
+            }
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// unit of measurement with its conversion to the base unit of its dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    /// symbol shown in results, e.g. `km`
+    pub symbol: &'static str,
+    pub singular: &'static str,
+    pub plural: &'static str,
+    pub dimension: Dimension,
+    /// multiplies a value in this unit, after adding the offset, into the base unit
+    pub factor: f64,
+    /// only temperatures like `°C` have one
+    pub offset: f64,
+    /// more names it's found by, next to the symbol and the singular and plural
+    aliases: &'static [&'static str],
+}
+
+impl Unit {
+    pub fn to_base(&self, value: f64) -> f64 {
+        (value + self.offset) * self.factor
+    }
+
+    pub fn from_base(&self, value: f64) -> f64 {
+        let value = value / self.factor - self.offset;
+        // rounding noise left after the offset cancelled out, `32 °F` is `0 °C`
+        if value.abs() < self.offset.abs() * 1e-12 {
+            return 0.0;
+        }
+        value
+    }
+}
+
+/// value in the base unit of its dimension, metres for a length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub dimension: Dimension,
+    /// unit with an offset it was given in, like `°C`, it can only be converted
+    pub scale: Option<&'static Unit>,
+}
+
+impl Quantity {
+    /// plain number without a unit
+    pub fn number(value: f64) -> Self {
+        Self {
+            value,
+            dimension: Dimension::NONE,
+            scale: None,
+        }
+    }
+
+    pub fn new(value: f64, unit: &'static Unit) -> Self {
+        Self {
+            value: unit.to_base(value),
+            dimension: unit.dimension,
+            scale: (unit.offset != 0.0).then_some(unit),
+        }
+    }
+
+    /// the value in the unit, which has to measure the same thing
+    pub fn value_in(&self, unit: &Unit) -> Result<f64, UnitError> {
+        self.same_dimension(unit.dimension)?;
+        Ok(unit.from_base(self.value))
+    }
+
+    /// sum of both, which have to measure the same thing
+    pub fn checked_add(self, other: Self) -> Result<Self, UnitError> {
+        let (left, right) = (self.calculable()?, other.calculable()?);
+        left.same_dimension(right.dimension)?;
+        Ok(Self {
+            value: left.value + right.value,
+            ..left
+        })
+    }
+
+    pub fn checked_sub(self, other: Self) -> Result<Self, UnitError> {
+        self.checked_add(Self {
+            value: -other.value,
+            ..other
+        })
+    }
+
+    pub fn checked_mul(self, other: Self) -> Result<Self, UnitError> {
+        let (left, right) = (self.calculable()?, other.calculable()?);
+        Ok(Self {
+            value: left.value * right.value,
+            dimension: left.dimension * right.dimension,
+            scale: None,
+        })
+    }
+
+    pub fn checked_div(self, other: Self) -> Result<Self, UnitError> {
+        self.checked_mul(other.checked_powi(-1)?)
+    }
+
+    pub fn checked_powi(self, power: i8) -> Result<Self, UnitError> {
+        let quantity = self.calculable()?;
+        Ok(Self {
+            value: quantity.value.powi(i32::from(power)),
+            dimension: quantity.dimension.powi(power),
+            scale: None,
+        })
+    }
+
+    /// `20 °C * 2` would double 293.15 K, temperatures with an offset are
+    /// points on their scale and only convert
+    fn calculable(self) -> Result<Self, UnitError> {
+        match self.scale {
+            Some(unit) => Err(UnitError::Scale(unit)),
+            None => Ok(self),
+        }
+    }
+
+    fn same_dimension(&self, dimension: Dimension) -> Result<(), UnitError> {
+        if self.dimension != dimension {
+            return Err(UnitError::Mismatch(self.dimension, dimension));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitError {
+    /// units measuring different things, like `km` and `kg`
+    Incompatible {
+        from: &'static Unit,
+        to: &'static Unit,
+    },
+    /// quantities of different dimensions added or converted, like `3 km + 2 kg`
+    Mismatch(Dimension, Dimension),
+    /// calculation with a unit that has an offset, like `10 °C + 5 °C`
+    Scale(&'static Unit),
+}
+
+impl std::fmt::Display for UnitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnitError::Incompatible { from, to } => write!(
+                f,
+                "{} is a {}, {} is a {}",
+                from.symbol, from.dimension, to.symbol, to.dimension
+            ),
+            UnitError::Mismatch(left, right) => write!(f, "a {} and a {}", left, right),
+            UnitError::Scale(unit) => write!(
+                f,
+                "{} is a point on a scale, calculate with K instead",
+                unit.symbol
+            ),
+        }
+    }
+}
+
+/// unit of uom with the symbol shown for it and more aliases
+///
+/// `* factor` scales a uom unit into one uom doesn't have, like a week
+macro_rules! unit {
+    ($dimension:ident, $quantity:ident::$unit:ident, $symbol:literal $(, $alias:literal)* $(,)?) => {
+        unit!(@build $dimension, $quantity::$unit, 1.0, $symbol,
+            <uom::si::$quantity::$unit as uom::si::Unit>::singular(),
+            <uom::si::$quantity::$unit as uom::si::Unit>::plural(),
+            [$($alias),*])
+    };
+    ($dimension:ident, $quantity:ident::$unit:ident * $factor:literal, $symbol:literal,
+        $singular:literal, $plural:literal $(, $alias:literal)* $(,)?) => {
+        unit!(@build $dimension, $quantity::$unit, $factor, $symbol, $singular, $plural, [$($alias),*])
+    };
+    (@build $dimension:ident, $quantity:ident::$unit:ident, $factor:expr, $symbol:literal,
+        $singular:expr, $plural:expr, [$($alias:literal),*]) => {
+        Unit {
+            symbol: $symbol,
+            singular: $singular,
+            plural: $plural,
+            dimension: Dimension::$dimension,
+            factor: <uom::si::$quantity::$unit as Conversion<f64>>::coefficient().value() * $factor,
+            offset: <uom::si::$quantity::$unit as Conversion<f64>>::constant(uom::ConstantOp::Add)
+                .value(),
+            aliases: &[$($alias),*],
+        }
+    };
+}
+
+/// units by their names, an exact name wins over one differing in case
+struct Registry {
+    units: Vec<Unit>,
+    exact: HashMap<String, usize>,
+    folded: HashMap<String, usize>,
+}
+
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+impl Registry {
+    fn new() -> Self {
+        let units = units();
+        let mut exact = HashMap::new();
+        let mut folded = HashMap::new();
+        for (index, unit) in units.iter().enumerate() {
+            let names = [unit.symbol, unit.singular, unit.plural]
+                .into_iter()
+                .chain(unit.aliases.iter().copied());
+            for name in names {
+                for name in spellings(name) {
+                    exact.entry(name.clone()).or_insert(index);
+                    // earlier units win, e.g. `mb` is a megabyte and not a megabit
+                    folded.entry(name.to_lowercase()).or_insert(index);
+                }
+            }
+        }
+        Self {
+            units,
+            exact,
+            folded,
+        }
+    }
+}
+
+/// the name and its british spelling, `kilometer` and `kilometre`
+fn spellings(name: &str) -> Vec<String> {
+    let mut spellings = vec![name.to_string()];
+    for (american, british) in [("meter", "metre"), ("liter", "litre")] {
+        if name.contains(american) {
+            spellings.push(name.replace(american, british));
+        }
+    }
+    spellings
+}
+
+/// unit by its symbol, name, plural or alias, like `km`, `kilometres` or `°C`
+pub fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.trim();
+    let registry = &*REGISTRY;
+    registry
+        .exact
+        .get(name)
+        .or_else(|| registry.folded.get(&name.to_lowercase()))
+        .map(|index| &registry.units[*index])
+}
+
+/// units results are shown in when none is asked for, the metric and the
+/// imperial ones from small to large
+const READABLE_UNITS: &[(Dimension, &[&str], &[&str])] = &[
+    (
+        Dimension::LENGTH,
+        &["mm", "cm", "m", "km"],
+        &["in", "ft", "mi"],
+    ),
+    (
+        Dimension::AREA,
+        &["mm²", "cm²", "m²", "km²"],
+        &["in²", "ft²", "mi²"],
+    ),
+    (Dimension::VOLUME, &["mL", "L", "m³"], &["fl oz", "gal"]),
+    (Dimension::MASS, &["mg", "g", "kg", "t"], &["oz", "lb"]),
+    (
+        Dimension::TIME,
+        &["ns", "µs", "ms", "s", "min", "h", "d", "yr"],
+        &[],
+    ),
+    (Dimension::FREQUENCY, &["Hz", "kHz", "MHz", "GHz"], &[]),
+    (Dimension::SPEED, &["km/h"], &["mph"]),
+    (Dimension::ENERGY, &["J", "kJ", "MJ", "GJ"], &[]),
+    (Dimension::POWER, &["mW", "W", "kW", "MW", "GW"], &[]),
+    (Dimension::PRESSURE, &["Pa", "kPa", "MPa"], &["psi"]),
+    (Dimension::TEMPERATURE, &["°C"], &["°F"]),
+    (Dimension::DATA, &["B", "kB", "MB", "GB", "TB", "PB"], &[]),
+    (Dimension::DATA_RATE, &["B/s", "kB/s", "MB/s", "GB/s"], &[]),
+    (Dimension::ANGLE, &["°"], &[]),
+];
+
+/// units that make results show in imperial units
+const IMPERIAL_UNITS: &[&str] = &[
+    "mi", "yd", "ft", "in", "lb", "oz", "st", "gal", "qt", "pt", "cup", "fl oz", "ft³", "in³",
+    "ac", "ft²", "in²", "yd²", "mi²", "mph", "ft/s", "psi", "°F",
+];
+
+/// unit showing the quantity with a number between 1 and 1000 if possible
+///
+/// the units the quantity was typed in come first, so `1 GiB + 512 MiB` stays
+/// in GiB, without one of them imperial units follow imperial input
+pub fn readable_unit(quantity: &Quantity, typed: &[&'static Unit]) -> Option<&'static Unit> {
+    let mut units = typed
+        .iter()
+        .filter(|unit| unit.dimension == quantity.dimension)
+        .copied()
+        .collect::<Vec<_>>();
+    if units.is_empty() {
+        let imperial = typed
+            .iter()
+            .any(|unit| IMPERIAL_UNITS.contains(&unit.symbol));
+        let (_, metric, imperial_units) = READABLE_UNITS
+            .iter()
+            .find(|(dimension, _, _)| *dimension == quantity.dimension)?;
+        let symbols = if imperial && !imperial_units.is_empty() {
+            imperial_units
+        } else {
+            metric
+        };
+        units = symbols
+            .iter()
+            .filter_map(|symbol| find_unit(symbol))
+            .collect();
+    }
+    units.sort_by(|a, b| a.factor.total_cmp(&b.factor));
+    units.dedup_by(|a, b| a.symbol == b.symbol);
+
+    let size = |unit: &Unit| unit.from_base(quantity.value).abs();
+    units
+        .iter()
+        .find(|unit| (1.0..1000.0).contains(&size(unit)))
+        .or_else(|| units.iter().rev().find(|unit| size(unit) >= 1.0))
+        .or(units.first())
+        .copied()
+}
+
+/// all known units, where two share a name the first one gets it
+fn units() -> Vec<Unit> {
+    vec![
+        // length
+        unit!(LENGTH, length::meter, "m"),
+        unit!(LENGTH, length::kilometer, "km"),
+        unit!(LENGTH, length::centimeter, "cm"),
+        unit!(LENGTH, length::millimeter, "mm"),
+        unit!(LENGTH, length::micrometer, "µm", "um", "μm"),
+        unit!(LENGTH, length::nanometer, "nm"),
+        unit!(LENGTH, length::mile, "mi"),
+        unit!(LENGTH, length::yard, "yd", "yds"),
+        unit!(LENGTH, length::foot, "ft", "'", "′"),
+        unit!(LENGTH, length::inch, "in", "\"", "″"),
+        unit!(LENGTH, length::nautical_mile, "nmi", "NM"),
+        unit!(LENGTH, length::light_year, "ly", "lightyear", "lightyears"),
+        unit!(LENGTH, length::astronomical_unit, "au"),
+        unit!(LENGTH, length::parsec, "pc"),
+        unit!(LENGTH, length::angstrom, "Å", "angstrom", "angstroms"),
+        unit!(LENGTH, length::mil, "mil", "thou"),
+        // mass
+        unit!(MASS, mass::kilogram, "kg", "kilo", "kilos"),
+        unit!(MASS, mass::gram, "g"),
+        unit!(MASS, mass::milligram, "mg"),
+        unit!(MASS, mass::microgram, "µg", "ug", "mcg"),
+        unit!(
+            MASS,
+            mass::ton,
+            "t",
+            "tonne",
+            "tonnes",
+            "metric ton",
+            "metric tons"
+        ),
+        unit!(MASS, mass::pound, "lb", "lbs"),
+        unit!(MASS, mass::ounce, "oz"),
+        unit!(MASS, mass::pound * 14.0, "st", "stone", "stones"),
+        unit!(MASS, mass::ton_short, "short ton", "us ton", "us tons"),
+        unit!(MASS, mass::ton_long, "long ton", "uk ton", "uk tons"),
+        unit!(MASS, mass::carat, "ct"),
+        unit!(MASS, mass::grain, "gr"),
+        // volume
+        unit!(VOLUME, volume::liter, "L", "l"),
+        unit!(VOLUME, volume::milliliter, "mL"),
+        unit!(VOLUME, volume::centiliter, "cL"),
+        unit!(VOLUME, volume::deciliter, "dL"),
+        unit!(
+            VOLUME,
+            volume::cubic_meter,
+            "m³",
+            "m3",
+            "m^3",
+            "cubic meters"
+        ),
+        unit!(VOLUME, volume::cubic_centimeter, "cm³", "cm3", "cc"),
+        unit!(VOLUME, volume::cubic_millimeter, "mm³", "mm3"),
+        unit!(VOLUME, volume::gallon, "gal", "us gal"),
+        unit!(VOLUME, volume::gallon_imperial, "imp gal", "uk gal"),
+        unit!(VOLUME, volume::quart_liquid, "qt", "quart", "quarts"),
+        unit!(VOLUME, volume::pint_liquid, "pt", "pint", "pints"),
+        unit!(VOLUME, volume::cup, "cup"),
+        unit!(VOLUME, volume::fluid_ounce, "fl oz", "floz"),
+        unit!(VOLUME, volume::tablespoon, "tbsp"),
+        unit!(VOLUME, volume::teaspoon, "tsp"),
+        unit!(VOLUME, volume::cubic_foot, "ft³", "ft3", "cu ft"),
+        unit!(VOLUME, volume::cubic_inch, "in³", "in3", "cu in"),
+        unit!(VOLUME, volume::barrel, "bbl"),
+        // area
+        unit!(AREA, area::square_meter, "m²", "m2", "m^2", "sqm", "sq m"),
+        unit!(AREA, area::square_kilometer, "km²", "km2", "km^2", "sq km"),
+        unit!(AREA, area::square_centimeter, "cm²", "cm2", "cm^2"),
+        unit!(AREA, area::square_millimeter, "mm²", "mm2", "mm^2"),
+        unit!(AREA, area::hectare, "ha"),
+        unit!(AREA, area::acre, "ac"),
+        unit!(
+            AREA,
+            area::square_foot,
+            "ft²",
+            "ft2",
+            "ft^2",
+            "sqft",
+            "sq ft"
+        ),
+        unit!(AREA, area::square_inch, "in²", "in2", "in^2", "sq in"),
+        unit!(AREA, area::square_yard, "yd²", "yd2", "yd^2", "sq yd"),
+        unit!(AREA, area::square_mile, "mi²", "mi2", "mi^2", "sq mi"),
+        // speed
+        unit!(SPEED, velocity::meter_per_second, "m/s", "mps"),
+        unit!(
+            SPEED,
+            velocity::kilometer_per_hour,
+            "km/h",
+            "kmh",
+            "kph",
+            "kmph"
+        ),
+        unit!(SPEED, velocity::mile_per_hour, "mph", "mi/h"),
+        unit!(SPEED, velocity::foot_per_second, "ft/s", "fps"),
+        unit!(SPEED, velocity::knot, "kn", "kt", "knots"),
+        // time
+        unit!(TIME, time::second, "s", "sec", "secs"),
+        unit!(TIME, time::millisecond, "ms"),
+        unit!(TIME, time::microsecond, "µs", "us", "μs"),
+        unit!(TIME, time::nanosecond, "ns"),
+        unit!(TIME, time::minute, "min", "mins"),
+        unit!(TIME, time::hour, "h", "hr", "hrs"),
+        unit!(TIME, time::day, "d"),
+        unit!(TIME, time::day * 7.0, "wk", "week", "weeks"),
+        unit!(TIME, time::year, "yr", "y", "yrs"),
+        // energy
+        unit!(ENERGY, energy::joule, "J"),
+        unit!(ENERGY, energy::kilojoule, "kJ"),
+        unit!(ENERGY, energy::megajoule, "MJ"),
+        unit!(ENERGY, energy::watt_hour, "Wh"),
+        unit!(ENERGY, energy::kilowatt_hour, "kWh"),
+        unit!(ENERGY, energy::megawatt_hour, "MWh"),
+        unit!(ENERGY, energy::calorie, "cal"),
+        unit!(ENERGY, energy::kilocalorie, "kcal", "Cal"),
+        unit!(ENERGY, energy::electronvolt, "eV"),
+        unit!(ENERGY, energy::btu_it, "BTU", "btus"),
+        // power
+        unit!(POWER, power::watt, "W"),
+        unit!(POWER, power::milliwatt, "mW"),
+        unit!(POWER, power::kilowatt, "kW"),
+        unit!(POWER, power::megawatt, "MW"),
+        unit!(POWER, power::gigawatt, "GW"),
+        unit!(POWER, power::horsepower, "hp"),
+        unit!(POWER, power::horsepower_metric, "PS", "metric hp"),
+        // pressure
+        unit!(PRESSURE, pressure::pascal, "Pa"),
+        unit!(PRESSURE, pressure::hectopascal, "hPa"),
+        unit!(PRESSURE, pressure::kilopascal, "kPa"),
+        unit!(PRESSURE, pressure::megapascal, "MPa"),
+        unit!(PRESSURE, pressure::bar, "bar", "bars"),
+        unit!(PRESSURE, pressure::millibar, "mbar"),
+        unit!(PRESSURE, pressure::atmosphere, "atm"),
+        unit!(PRESSURE, pressure::psi, "psi"),
+        unit!(PRESSURE, pressure::torr, "Torr"),
+        unit!(PRESSURE, pressure::millimeter_of_mercury, "mmHg"),
+        // data, SI prefixes count in 1000 and IEC ones like `KiB` in 1024
+        unit!(DATA, information::byte, "B"),
+        unit!(DATA, information::kilobyte, "kB", "KB"),
+        unit!(DATA, information::megabyte, "MB"),
+        unit!(DATA, information::gigabyte, "GB"),
+        unit!(DATA, information::terabyte, "TB"),
+        unit!(DATA, information::petabyte, "PB"),
+        unit!(DATA, information::kibibyte, "KiB"),
+        unit!(DATA, information::mebibyte, "MiB"),
+        unit!(DATA, information::gibibyte, "GiB"),
+        unit!(DATA, information::tebibyte, "TiB"),
+        unit!(DATA, information::pebibyte, "PiB"),
+        unit!(DATA, information::bit, "bit"),
+        unit!(DATA, information::kilobit, "kbit"),
+        unit!(DATA, information::megabit, "Mbit", "Mb"),
+        unit!(DATA, information::gigabit, "Gbit", "Gb"),
+        unit!(DATA, information::terabit, "Tbit", "Tb"),
+        unit!(DATA, information::kibibit, "Kibit", "Kib"),
+        unit!(DATA, information::mebibit, "Mibit", "Mib"),
+        unit!(DATA, information::gibibit, "Gibit", "Gib"),
+        // data rate
+        unit!(DATA_RATE, information_rate::byte_per_second, "B/s"),
+        unit!(
+            DATA_RATE,
+            information_rate::kilobyte_per_second,
+            "kB/s",
+            "KB/s"
+        ),
+        unit!(DATA_RATE, information_rate::megabyte_per_second, "MB/s"),
+        unit!(DATA_RATE, information_rate::gigabyte_per_second, "GB/s"),
+        unit!(DATA_RATE, information_rate::kibibyte_per_second, "KiB/s"),
+        unit!(DATA_RATE, information_rate::mebibyte_per_second, "MiB/s"),
+        unit!(DATA_RATE, information_rate::gibibyte_per_second, "GiB/s"),
+        unit!(DATA_RATE, information_rate::bit_per_second, "bit/s", "bps"),
+        unit!(
+            DATA_RATE,
+            information_rate::kilobit_per_second,
+            "kbit/s",
+            "kbps"
+        ),
+        unit!(
+            DATA_RATE,
+            information_rate::megabit_per_second,
+            "Mbit/s",
+            "Mb/s",
+            "Mbps"
+        ),
+        unit!(
+            DATA_RATE,
+            information_rate::gigabit_per_second,
+            "Gbit/s",
+            "Gb/s",
+            "Gbps"
+        ),
+        // angle
+        unit!(ANGLE, angle::degree, "°", "deg", "degs"),
+        unit!(ANGLE, angle::radian, "rad"),
+        unit!(ANGLE, angle::gon, "grad", "gradian", "gradians"),
+        unit!(ANGLE, angle::revolution, "rev", "turn", "turns"),
+        unit!(ANGLE, angle::minute, "arcmin"),
+        unit!(ANGLE, angle::second, "arcsec"),
+        // frequency
+        unit!(FREQUENCY, frequency::hertz, "Hz"),
+        unit!(FREQUENCY, frequency::kilohertz, "kHz"),
+        unit!(FREQUENCY, frequency::megahertz, "MHz"),
+        unit!(FREQUENCY, frequency::gigahertz, "GHz"),
+        unit!(FREQUENCY, frequency::cycle_per_minute, "rpm"),
+        // temperature
+        unit!(TEMPERATURE, thermodynamic_temperature::kelvin, "K"),
+        unit!(
+            TEMPERATURE,
+            thermodynamic_temperature::degree_celsius,
+            "°C",
+            "C",
+            "℃",
+            "ºC",
+            "degC",
+            "celsius"
+        ),
+        unit!(
+            TEMPERATURE,
+            thermodynamic_temperature::degree_fahrenheit,
+            "°F",
+            "F",
+            "℉",
+            "ºF",
+            "degF",
+            "fahrenheit"
+        ),
+        unit!(
+            TEMPERATURE,
+            thermodynamic_temperature::degree_rankine,
+            "°R",
+            "rankine"
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(name: &str) -> &'static Unit {
+        find_unit(name).unwrap_or_else(|| panic!("unknown unit {}", name))
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn finds_units_by_symbol_name_and_plural() {
+        assert_eq!(unit("kilometres").symbol, "km");
+        assert_eq!(unit("kilometer").symbol, "km");
+        assert_eq!(unit("lbs").symbol, "lb");
+        assert_eq!(unit("fahrenheit").symbol, "°F");
+        assert!(find_unit("stars").is_none());
+    }
+
+    #[test]
+    fn exact_case_wins_over_folded() {
+        assert_eq!(unit("Mb").dimension, Dimension::DATA);
+        assert_eq!(unit("Mb").symbol, "Mbit");
+        assert_eq!(unit("mb").symbol, "MB");
+    }
+
+    #[test]
+    fn converts_within_a_dimension() {
+        let distance = Quantity::new(10.0, unit("km"));
+        assert_close(distance.value_in(unit("mi")).unwrap(), 6.213_711_922_373_34);
+
+        let data = Quantity::new(1.0, unit("GiB"));
+        assert_close(data.value_in(unit("MiB")).unwrap(), 1024.0);
+    }
+
+    #[test]
+    fn converts_temperatures_with_offsets() {
+        let boiling = Quantity::new(212.0, unit("°F"));
+        assert_close(boiling.value_in(unit("°C")).unwrap(), 100.0);
+        assert_close(boiling.value_in(unit("K")).unwrap(), 373.15);
+
+        let freezing = Quantity::new(32.0, unit("°F"));
+        assert_eq!(freezing.value_in(unit("°C")).unwrap(), 0.0);
+
+        let cold = Quantity::new(-40.0, unit("°C"));
+        assert_close(cold.value_in(unit("°F")).unwrap(), -40.0);
+    }
+
+    #[test]
+    fn refuses_conversion_between_dimensions() {
+        let distance = Quantity::new(5.0, unit("km"));
+        assert_eq!(
+            distance.value_in(unit("kg")),
+            Err(UnitError::Mismatch(Dimension::LENGTH, Dimension::MASS))
+        );
+        assert_eq!(
+            distance
+                .checked_add(Quantity::new(2.0, unit("kg")))
+                .unwrap_err()
+                .to_string(),
+            "a length and a mass"
+        );
+    }
+
+    #[test]
+    fn combines_dimensions() {
+        let speed = Quantity::new(60.0, unit("mph"));
+        let distance = speed.checked_mul(Quantity::new(2.5, unit("h"))).unwrap();
+        assert_eq!(distance.dimension, Dimension::LENGTH);
+        assert_close(distance.value_in(unit("mi")).unwrap(), 150.0);
+
+        let area = Quantity::new(3.0, unit("m")).checked_powi(2).unwrap();
+        assert_eq!(area.dimension, Dimension::AREA);
+        assert_eq!(unit("km/h").dimension, Dimension::LENGTH / Dimension::TIME);
+    }
+
+    #[test]
+    fn refuses_arithmetic_on_offset_units() {
+        let warm = Quantity::new(20.0, unit("°C"));
+        assert_eq!(
+            warm.checked_add(Quantity::new(5.0, unit("°C"))),
+            Err(UnitError::Scale(unit("°C")))
+        );
+        assert_eq!(
+            warm.checked_mul(Quantity::number(2.0)),
+            Err(UnitError::Scale(unit("°C")))
+        );
+        assert_eq!(
+            Quantity::new(10.0, unit("K")).checked_sub(warm),
+            Err(UnitError::Scale(unit("°C")))
+        );
+
+        let difference = Quantity::new(10.0, unit("K"))
+            .checked_add(Quantity::new(5.0, unit("K")))
+            .unwrap();
+        assert_close(difference.value_in(unit("K")).unwrap(), 15.0);
+    }
+
+    #[test]
+    fn readable_unit_keeps_numbers_small() {
+        let length = Quantity::new(3250.0, unit("m"));
+        assert_eq!(readable_unit(&length, &[]).unwrap().symbol, "km");
+
+        let tiny = Quantity::new(0.5, unit("cm"));
+        assert_eq!(readable_unit(&tiny, &[]).unwrap().symbol, "mm");
+    }
+
+    #[test]
+    fn readable_unit_prefers_typed_units() {
+        let data = Quantity::new(1.5, unit("GiB"));
+        let typed = [unit("GiB"), unit("MiB")];
+        assert_eq!(readable_unit(&data, &typed).unwrap().symbol, "GiB");
+
+        let distance = Quantity::new(150.0, unit("mi"));
+        let typed = [unit("mph"), unit("h")];
+        assert_eq!(readable_unit(&distance, &typed).unwrap().symbol, "mi");
+    }
+}
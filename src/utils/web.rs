@@ -1,8 +1,12 @@
-use std::process::Command;
+use std::collections::HashSet;
+use std::time::Duration;
 
-use indexmap::IndexMap;
-
-use crate::utils::logger::{LogLevel, Logger};
+use crate::utils::{
+    config::CONFIG,
+    engines::{ENGINES, SearchEngine},
+    logger::{LogLevel, Logger},
+    suggestions::fetch_suggestions,
+};
 
 lazy_static::lazy_static! {
     static ref LOG: Logger = Logger::new("web", LogLevel::Debug);
@@ -14,64 +18,149 @@ pub struct WebSearchResult {
     pub url: String,
     pub description: String,
     pub search_engine: String,
+    /// icon file path or theme icon name of the engine
+    pub icon: Option<String>,
 }
 
 pub struct WebSearchManager {
-    search_engines: IndexMap<String, String>,
+    search_engines: Vec<&'static SearchEngine>,
+}
+
+impl Default for WebSearchManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WebSearchManager {
+    /// enabled engines of the engine config
     pub fn new() -> Self {
-        let mut search_engines = IndexMap::new();
-
-        search_engines.insert(
-            "duckduckgo".to_string(),
-            "https://duckduckgo.com/?q={}".to_string(),
-        );
-        search_engines.insert(
-            "google".to_string(),
-            "https://www.google.com/search?q={}".to_string(),
-        );
-        search_engines.insert(
-            "youtube".to_string(),
-            "https://www.youtube.com/results?search_query={}".to_string(),
-        );
-        search_engines.insert(
-            "stackoverflow".to_string(),
-            "https://stackoverflow.com/search?q={}".to_string(),
-        );
-
-        Self { search_engines }
+        Self {
+            search_engines: ENGINES.enabled(),
+        }
     }
 
     pub fn get_search_engines(&self) -> Vec<String> {
-        self.search_engines.keys().cloned().collect()
+        self.search_engines
+            .iter()
+            .map(|engine| engine.id.clone())
+            .collect()
     }
 
     pub fn search_engines_for_query(&self, query: &str) -> Vec<WebSearchResult> {
-        let mut results = Vec::new();
+        self.search_engines
+            .iter()
+            .map(|engine| engine_result(engine, query))
+            .collect()
+    }
 
-        for (engine_name, url_template) in &self.search_engines {
-            let description = match engine_name.as_str() {
-                "duckduckgo" => "Search with DuckDuckGo (Privacy-focused)",
-                "google" => "Search with Google",
-                "youtube" => "Search YouTube videos",
-                "stackoverflow" => "Search Stack Overflow",
-                _ => "Web search",
-            };
-            results.push(WebSearchResult {
-                title: format!("Search \"{}\" on {}", query, engine_name),
-                url: url_template.replace("{}", &urlencoding::encode(query)),
-                description: description.to_string(),
-                search_engine: engine_name.clone(),
-            });
+    /// results for a web mode query
+    ///
+    /// a bang (`!yt lofi` or `lofi !yt`) searches only on its engine, a bare
+    /// keyword (`yt lofi`) puts its engine on top of the usual list
+    pub fn results_for_query(&self, query: &str) -> Vec<WebSearchResult> {
+        self.engines_for_query(query)
+            .into_iter()
+            .map(|(engine, query)| engine_result(engine, query))
+            .collect()
+    }
+
+    /// single result for a query with a bang, like `!so borrow checker`
+    pub fn bang_result(&self, query: &str) -> Option<WebSearchResult> {
+        parse_bang(query).map(|(engine, query)| engine_result(engine, query))
+    }
+
+    /// suggestions of the engines listed for the query, in the same order
+    ///
+    /// engines are asked at the same time, each one gets the configured timeout
+    pub async fn suggestions(&self, query: &str) -> Vec<WebSearchResult> {
+        let timeout = Duration::from_millis(CONFIG.search.suggestion_timeout_ms);
+
+        let mut requests: Vec<(&SearchEngine, &str, String)> = Vec::new();
+        for (engine, query) in self.engines_for_query(query) {
+            // a keyword engine is listed twice, ask it with the query it searches first
+            if requests.iter().any(|(other, _, _)| other.id == engine.id) {
+                continue;
+            }
+            if !query.is_empty()
+                && let Some(url) = engine.suggestions_url(query)
+            {
+                requests.push((engine, query, url));
+            }
         }
 
+        let fetched = futures::future::join_all(
+            requests
+                .iter()
+                .map(|(_, _, url)| fetch_suggestions(url, timeout)),
+        )
+        .await;
+
+        // the same suggestion of several engines is only shown once
+        let mut seen = HashSet::new();
+        let mut results = Vec::new();
+        for ((engine, query, _), suggestions) in requests.iter().zip(fetched) {
+            let suggestions = suggestions
+                .into_iter()
+                .filter(|suggestion| !suggestion.eq_ignore_ascii_case(query.trim()))
+                .filter(|suggestion| seen.insert(suggestion.to_lowercase()))
+                .take(CONFIG.search.max_suggestions);
+            results.extend(suggestions.map(|suggestion| WebSearchResult {
+                url: engine.search_url(&suggestion),
+                description: format!("Suggested by {}", engine.name),
+                search_engine: engine.id.clone(),
+                icon: engine.icon.clone(),
+                title: suggestion,
+            }));
+        }
+
+        LOG.debug(&format!("{} suggestions for '{}'", results.len(), query));
         results
     }
 
-    pub fn open_url(&self, url: &str) -> Result<(), Box<dyn std::error::Error>> {
-        Command::new("xdg-open").arg(url).spawn()?;
-        Ok(())
+    /// engines to search on with the query each of them gets
+    fn engines_for_query<'a>(&self, query: &'a str) -> Vec<(&'static SearchEngine, &'a str)> {
+        if let Some(bang) = parse_bang(query) {
+            return vec![bang];
+        }
+
+        let keyword_engine = query
+            .split_once(char::is_whitespace)
+            .and_then(|(keyword, rest)| Some((ENGINES.by_engine_keyword(keyword)?, rest.trim())));
+        if let Some(keyword_engine) = keyword_engine {
+            return vec![keyword_engine];
+        }
+
+        self.search_engines
+            .iter()
+            .map(|engine| (*engine, query))
+            .collect()
+    }
+}
+
+/// engine of the bang in the query and the query without it
+fn parse_bang(query: &str) -> Option<(&'static SearchEngine, &str)> {
+    let query = query.trim();
+    let words = query.split_whitespace().collect::<Vec<_>>();
+    let (first, last) = (words.first()?, words.last()?);
+
+    let (keyword, rest) = if let Some(keyword) = first.strip_prefix('!') {
+        (keyword, query[first.len()..].trim())
+    } else if let Some(keyword) = last.strip_prefix('!') {
+        (keyword, query[..query.len() - last.len()].trim())
+    } else {
+        return None;
+    };
+
+    ENGINES.by_keyword(keyword).map(|engine| (engine, rest))
+}
+
+fn engine_result(engine: &SearchEngine, query: &str) -> WebSearchResult {
+    WebSearchResult {
+        title: format!("Search \"{}\" on {}", query, engine.name),
+        url: engine.search_url(query),
+        description: engine.description.clone(),
+        search_engine: engine.id.clone(),
+        icon: engine.icon.clone(),
     }
 }